@@ -1,14 +1,68 @@
-use crate::app_error::AppError;
+use crate::{
+    C, S,
+    app_error::AppError,
+    interface::{InterfaceFilter, InterfaceRule},
+    ip_provider::{IpProvider, IpProviders, PROVIDERS_NONE},
+    notifier::{
+        Backend, Discord, Email, Gotify, MQTT_TOPIC, Mqtt, NTFY_URL, Notifier, Ntfy, NtfyAuth,
+        PUSHOVER_URL, ParseMode, Pushover, PushoverFormat, PushoverOptions, SMTP_SUBJECT, Slack,
+        SmtpSecurity, TELEGRAM_URL, Telegram, WEBHOOK_BODY, Webhook,
+    },
+    secret::Secret,
+};
 use directories::BaseDirs;
 use jiff::tz::TimeZone;
 use std::{
     collections::HashMap,
     env,
     path::{Path, PathBuf},
+    str::FromStr,
 };
 
 type EnvHashMap = HashMap<String, String>;
 
+/// How many messages can be sent in each window, service install & uninstall events have their own allowance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Online messages allowed per window
+    pub max: usize,
+    /// Service install & uninstall messages allowed per window
+    pub max_service: usize,
+    /// Length of the window, in seconds
+    pub window: u64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max: 6,
+            max_service: 6,
+            window: 60 * 60,
+        }
+    }
+}
+
+/// N boots within the window marks the machine as looping, alerts then stop until no boot has happened for the stable period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BootLoop {
+    /// Boots needed to be considered a loop, 0 disables detection
+    pub count: usize,
+    /// Length of the window, in seconds
+    pub window: u64,
+    /// Seconds without a boot before the loop is over
+    pub stable: u64,
+}
+
+impl Default for BootLoop {
+    fn default() -> Self {
+        Self {
+            count: 5,
+            window: 60 * 15,
+            stable: 60 * 60,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AppEnv {
     pub location_base: PathBuf,
@@ -16,9 +70,22 @@ pub struct AppEnv {
     pub location_lock: PathBuf,
     pub log_level: tracing::Level,
     pub timezone: TimeZone,
-    pub token_app: String,
-    pub token_user: String,
+    pub notifiers: Vec<Backend>,
+    /// Tried in order, stopping at the first success, when none of the notifiers delivered
+    pub fallbacks: Vec<Backend>,
     pub machine_name: String,
+    pub rate_limit: RateLimit,
+    pub boot_loop: BootLoop,
+    /// Seconds between each heartbeat when running as a daemon, 0 disables
+    pub heartbeat: u64,
+    /// Seconds between each check of the public ips when running as a daemon, 0 disables
+    pub ip_check: u64,
+    /// Seconds between each check of the network interfaces when running as a daemon, 0 disables
+    pub interface_check: u64,
+    /// Which interfaces, and addresses, are reported, and watched for changes
+    pub interface_filter: InterfaceFilter,
+    /// Where the public ips are looked up
+    pub ip_providers: IpProviders,
 }
 
 impl AppEnv {
@@ -92,6 +159,98 @@ impl AppEnv {
             })
     }
 
+    /// Same as `parse_string`, but the value is never shown in logs
+    fn parse_secret(key: &str, map: &EnvHashMap) -> Result<Secret, AppError> {
+        Self::parse_string(key, map).map(Secret::from)
+    }
+
+    /// Get an optional env, an empty value is treated as missing
+    fn parse_optional(key: &str, map: &EnvHashMap) -> Option<String> {
+        map.get(key)
+            .filter(|value| !value.trim().is_empty())
+            .map(|value| S!(value.trim()))
+    }
+
+    /// Split a comma separated env into a vec, ignoring empty entries
+    fn parse_list(key: &str, map: &EnvHashMap) -> Vec<String> {
+        map.get(key).map_or_else(Vec::new, |value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|i| !i.is_empty())
+                .map(String::from)
+                .collect()
+        })
+    }
+
+    /// Parse a number, using the default if missing, but error if present and invalid
+    fn parse_number<T: FromStr>(key: &str, map: &EnvHashMap, default: T) -> Result<T, AppError> {
+        Self::parse_optional(key, map).map_or(Ok(default), |value| {
+            value
+                .parse::<T>()
+                .map_err(|_| AppError::InvalidEnv(key.into()))
+        })
+    }
+
+    /// Messages allowed per window, each missing env uses the default
+    fn parse_rate_limit(map: &EnvHashMap) -> Result<RateLimit, AppError> {
+        let default = RateLimit::default();
+        Ok(RateLimit {
+            max: Self::parse_number("RATE_LIMIT_MAX", map, default.max)?,
+            max_service: Self::parse_number("RATE_LIMIT_SERVICE_MAX", map, default.max_service)?,
+            window: Self::parse_number("RATE_LIMIT_WINDOW", map, default.window)?,
+        })
+    }
+
+    /// Boot loop detection, each missing env uses the default
+    fn parse_boot_loop(map: &EnvHashMap) -> Result<BootLoop, AppError> {
+        let default = BootLoop::default();
+        Ok(BootLoop {
+            count: Self::parse_number("BOOT_LOOP_COUNT", map, default.count)?,
+            window: Self::parse_number("BOOT_LOOP_WINDOW", map, default.window)?,
+            stable: Self::parse_number("BOOT_LOOP_STABLE", map, default.stable)?,
+        })
+    }
+
+    /// Comma separated interface name globs & cidrs, error if any entry is invalid
+    fn parse_interface_rules(key: &str, map: &EnvHashMap) -> Result<Vec<InterfaceRule>, AppError> {
+        Self::parse_list(key, map)
+            .iter()
+            .map(|i| i.parse().map_err(|()| AppError::InvalidEnv(S!(key))))
+            .collect()
+    }
+
+    fn parse_interface_filter(map: &EnvHashMap) -> Result<InterfaceFilter, AppError> {
+        Ok(InterfaceFilter {
+            include: Self::parse_interface_rules("INTERFACE_INCLUDE", map)?,
+            exclude: Self::parse_interface_rules("INTERFACE_EXCLUDE", map)?,
+        })
+    }
+
+    /// Comma separated providers, tried in order, missing uses the default, `none` disables the lookup
+    fn parse_ip_provider_list(
+        key: &str,
+        map: &EnvHashMap,
+        default: Vec<IpProvider>,
+    ) -> Result<Vec<IpProvider>, AppError> {
+        match Self::parse_optional(key, map) {
+            None => Ok(default),
+            Some(value) if value.eq_ignore_ascii_case(PROVIDERS_NONE) => Ok(vec![]),
+            Some(_) => Self::parse_list(key, map)
+                .iter()
+                .map(|i| i.parse().map_err(|()| AppError::InvalidEnv(S!(key))))
+                .collect(),
+        }
+    }
+
+    fn parse_ip_providers(map: &EnvHashMap) -> Result<IpProviders, AppError> {
+        let default = IpProviders::default();
+        Ok(IpProviders {
+            v4: Self::parse_ip_provider_list("IPV4_PROVIDERS", map, default.v4)?,
+            v6: Self::parse_ip_provider_list("IPV6_PROVIDERS", map, default.v6)?,
+        })
+    }
+
     /// Check that a given timezone is valid, else return UTC
     fn parse_timezone(map: &EnvHashMap) -> TimeZone {
         map.get("TIMEZONE").map_or(TimeZone::UTC, |s| {
@@ -99,6 +258,299 @@ impl AppEnv {
         })
     }
 
+    /// Pushover message options for a single event type, each `PUSHOVER_{event}_*` env takes precedence over the `PUSHOVER_*` env
+    /// A `PUSHOVER_PRIORITY` of 2 is emergency, which repeats every `PUSHOVER_RETRY` seconds until acknowledged, or `PUSHOVER_EXPIRE` seconds have passed
+    /// `PUSHOVER_FORMAT` can be `html` or `monospace`, the supplementary url is `PUSHOVER_LINK`, as `PUSHOVER_URL` is the api
+    fn parse_pushover_options(event: &str, map: &EnvHashMap) -> Result<PushoverOptions, AppError> {
+        let key = |name: &str| {
+            let key = format!("PUSHOVER_{event}_{name}");
+            if Self::parse_optional(&key, map).is_some() {
+                key
+            } else {
+                format!("PUSHOVER_{name}")
+            }
+        };
+        let priority_key = key("PRIORITY");
+        let priority = Self::parse_number(&priority_key, map, 0)?;
+        if !(-2..=2).contains(&priority) {
+            return Err(AppError::InvalidEnv(priority_key));
+        }
+        let retry_key = key("RETRY");
+        let retry = Self::parse_number(&retry_key, map, 60)?;
+        if retry < 30 {
+            return Err(AppError::InvalidEnv(retry_key));
+        }
+        let expire_key = key("EXPIRE");
+        let expire = Self::parse_number(&expire_key, map, 3600)?;
+        if !(1..=10800).contains(&expire) {
+            return Err(AppError::InvalidEnv(expire_key));
+        }
+        let format_key = key("FORMAT");
+        let format = match Self::parse_optional(&format_key, map)
+            .map(|i| i.to_lowercase())
+            .as_deref()
+        {
+            None | Some("plain") => PushoverFormat::Plain,
+            Some("html") => PushoverFormat::Html,
+            Some("monospace") => PushoverFormat::Monospace,
+            Some(_) => return Err(AppError::InvalidEnv(format_key)),
+        };
+        let ttl_key = key("TTL");
+        let ttl = Self::parse_optional(&ttl_key, map)
+            .map(|i| {
+                i.parse::<u64>()
+                    .ok()
+                    .filter(|i| *i > 0)
+                    .ok_or_else(|| AppError::InvalidEnv(C!(ttl_key)))
+            })
+            .transpose()?;
+        Ok(PushoverOptions {
+            title: Self::parse_optional(&key("TITLE"), map),
+            priority,
+            retry,
+            expire,
+            sound: Self::parse_optional(&key("SOUND"), map),
+            devices: Self::parse_list(&key("DEVICE"), map),
+            url: Self::parse_optional(&key("LINK"), map),
+            url_title: Self::parse_optional(&key("LINK_TITLE"), map),
+            format,
+            ttl,
+            timestamp: Self::parse_boolean(&key("TIMESTAMP"), map),
+        })
+    }
+
+    /// ntfy needs a topic, everything else is optional, a token takes precedence over a username & password
+    fn parse_ntfy(map: &EnvHashMap) -> Result<Ntfy, AppError> {
+        let auth = match (
+            Self::parse_optional("NTFY_TOKEN", map),
+            Self::parse_optional("NTFY_USERNAME", map),
+        ) {
+            (Some(token), _) => Some(NtfyAuth::Token(Secret::from(token))),
+            (None, Some(username)) => Some(NtfyAuth::Basic {
+                username,
+                password: Self::parse_secret("NTFY_PASSWORD", map)?,
+            }),
+            (None, None) => None,
+        };
+        let priority = Self::parse_number("NTFY_PRIORITY", map, 3)?;
+        if !(1..=5).contains(&priority) {
+            return Err(AppError::InvalidEnv(S!("NTFY_PRIORITY")));
+        }
+        Ok(Ntfy {
+            url: Self::parse_optional("NTFY_URL", map).unwrap_or_else(|| S!(NTFY_URL)),
+            topic: Self::parse_string("NTFY_TOPIC", map)?,
+            auth,
+            priority,
+            tags: Self::parse_list("NTFY_TAGS", map),
+        })
+    }
+
+    /// Gotify needs a server url and an app token
+    fn parse_gotify(map: &EnvHashMap) -> Result<Gotify, AppError> {
+        let priority = Self::parse_number("GOTIFY_PRIORITY", map, 5)?;
+        if priority > 10 {
+            return Err(AppError::InvalidEnv(S!("GOTIFY_PRIORITY")));
+        }
+        Ok(Gotify {
+            url: Self::parse_string("GOTIFY_URL", map)?,
+            token: Self::parse_secret("GOTIFY_TOKEN", map)?,
+            title: Self::parse_optional("GOTIFY_TITLE", map),
+            priority,
+            markdown: Self::parse_boolean("GOTIFY_MARKDOWN", map),
+        })
+    }
+
+    /// Telegram needs a bot token and a chat id, `TELEGRAM_PARSE_MODE` can be `html` or `markdownv2`
+    fn parse_telegram(map: &EnvHashMap) -> Result<Telegram, AppError> {
+        let parse_mode = match Self::parse_optional("TELEGRAM_PARSE_MODE", map)
+            .map(|i| i.to_lowercase())
+            .as_deref()
+        {
+            None => ParseMode::Plain,
+            Some("html") => ParseMode::Html,
+            Some("markdownv2") => ParseMode::MarkdownV2,
+            Some(_) => return Err(AppError::InvalidEnv(S!("TELEGRAM_PARSE_MODE"))),
+        };
+        Ok(Telegram {
+            url: Self::parse_optional("TELEGRAM_URL", map).unwrap_or_else(|| S!(TELEGRAM_URL)),
+            token: Self::parse_secret("TELEGRAM_TOKEN", map)?,
+            chat_id: Self::parse_string("TELEGRAM_CHAT_ID", map)?,
+            thread_id: Self::parse_optional("TELEGRAM_THREAD_ID", map)
+                .map(|i| {
+                    i.parse::<i64>()
+                        .map_err(|_| AppError::InvalidEnv(S!("TELEGRAM_THREAD_ID")))
+                })
+                .transpose()?,
+            parse_mode,
+        })
+    }
+
+    /// Generic webhook, `WEBHOOK_HEADERS` is a semicolon separated list of `Name: value` pairs
+    fn parse_webhook(map: &EnvHashMap) -> Result<Webhook, AppError> {
+        let method = Self::parse_optional("WEBHOOK_METHOD", map).map_or(
+            Ok(reqwest::Method::POST),
+            |method| {
+                reqwest::Method::from_bytes(method.to_uppercase().as_bytes())
+                    .map_err(|_| AppError::InvalidEnv(S!("WEBHOOK_METHOD")))
+            },
+        )?;
+        let headers = Self::parse_optional("WEBHOOK_HEADERS", map)
+            .unwrap_or_default()
+            .split(';')
+            .filter(|i| !i.trim().is_empty())
+            .map(|header| {
+                header
+                    .split_once(':')
+                    .map(|(name, value)| (S!(name.trim()), Secret::from(value.trim())))
+                    .ok_or_else(|| AppError::InvalidEnv(S!("WEBHOOK_HEADERS")))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Webhook {
+            url: Self::parse_secret("WEBHOOK_URL", map)?,
+            method,
+            headers,
+            body: Self::parse_optional("WEBHOOK_BODY", map).unwrap_or_else(|| S!(WEBHOOK_BODY)),
+        })
+    }
+
+    /// Email via smtp, `SMTP_SECURITY` can be `starttls` (default), `tls`, or `plain`
+    fn parse_email(map: &EnvHashMap) -> Result<Email, AppError> {
+        let security = match Self::parse_optional("SMTP_SECURITY", map)
+            .map(|i| i.to_lowercase())
+            .as_deref()
+        {
+            None | Some("starttls") => SmtpSecurity::StartTls,
+            Some("tls") => SmtpSecurity::Tls,
+            Some("plain") => SmtpSecurity::Plain,
+            Some(_) => return Err(AppError::InvalidEnv(S!("SMTP_SECURITY"))),
+        };
+        let credentials = Self::parse_optional("SMTP_USERNAME", map)
+            .map(|username| {
+                Self::parse_secret("SMTP_PASSWORD", map).map(|password| (username, password))
+            })
+            .transpose()?;
+        let from = Self::parse_string("SMTP_FROM", map)?
+            .parse()
+            .map_err(|_| AppError::InvalidEnv(S!("SMTP_FROM")))?;
+        let to = Self::parse_list("SMTP_TO", map)
+            .iter()
+            .map(|i| i.parse().map_err(|_| AppError::InvalidEnv(S!("SMTP_TO"))))
+            .collect::<Result<Vec<_>, _>>()?;
+        if to.is_empty() {
+            return Err(AppError::MissingEnv(S!("SMTP_TO")));
+        }
+        Ok(Email {
+            host: Self::parse_string("SMTP_HOST", map)?,
+            port: Self::parse_number("SMTP_PORT", map, security.default_port())?,
+            security,
+            credentials,
+            from,
+            to,
+            subject: Self::parse_optional("SMTP_SUBJECT", map).unwrap_or_else(|| S!(SMTP_SUBJECT)),
+        })
+    }
+
+    /// MQTT broker, `MQTT_QOS` is `0`, `1` (default), or `2`
+    /// The machine name is a level of each topic, and part of the client id, so can't contain a `/`, or either wildcard
+    fn parse_mqtt(map: &EnvHashMap) -> Result<Mqtt, AppError> {
+        if Self::parse_optional("MACHINE_NAME", map).is_some_and(|i| i.contains(['/', '+', '#'])) {
+            return Err(AppError::InvalidEnv(S!("MACHINE_NAME")));
+        }
+        let topic = Self::parse_optional("MQTT_TOPIC", map).unwrap_or_else(|| S!(MQTT_TOPIC));
+        if topic.contains(['+', '#']) {
+            return Err(AppError::InvalidEnv(S!("MQTT_TOPIC")));
+        }
+        let tls = Self::parse_boolean("MQTT_TLS", map);
+        let qos = match Self::parse_number("MQTT_QOS", map, 1)? {
+            0 => rumqttc::QoS::AtMostOnce,
+            1 => rumqttc::QoS::AtLeastOnce,
+            2 => rumqttc::QoS::ExactlyOnce,
+            _ => return Err(AppError::InvalidEnv(S!("MQTT_QOS"))),
+        };
+        let credentials = Self::parse_optional("MQTT_USERNAME", map)
+            .map(|username| {
+                Self::parse_secret("MQTT_PASSWORD", map).map(|password| (username, password))
+            })
+            .transpose()?;
+        Ok(Mqtt {
+            host: Self::parse_string("MQTT_HOST", map)?,
+            port: Self::parse_number("MQTT_PORT", map, if tls { 8883 } else { 1883 })?,
+            tls,
+            credentials,
+            topic,
+            qos,
+        })
+    }
+
+    /// Get a single notifier backend by name, `key` is the env the name came from
+    fn parse_notifier(key: &str, name: &str, map: &EnvHashMap) -> Result<Backend, AppError> {
+        match name.to_lowercase().as_str() {
+            "pushover" => Ok(Backend::Pushover(Pushover {
+                url: Self::parse_optional("PUSHOVER_URL", map).unwrap_or_else(|| S!(PUSHOVER_URL)),
+                token_app: Self::parse_secret("TOKEN_APP", map)?,
+                token_user: Self::parse_secret("TOKEN_USER", map)?,
+                online: Self::parse_pushover_options("ONLINE", map)?,
+                service: Self::parse_pushover_options("SERVICE", map)?,
+                status: Self::parse_pushover_options("STATUS", map)?,
+            })),
+            "discord" => Ok(Backend::Discord(Discord {
+                url: Self::parse_secret("DISCORD_WEBHOOK_URL", map)?,
+            })),
+            "email" => Ok(Backend::Email(Self::parse_email(map)?)),
+            "gotify" => Ok(Backend::Gotify(Self::parse_gotify(map)?)),
+            "mqtt" => Ok(Backend::Mqtt(Self::parse_mqtt(map)?)),
+            "ntfy" => Ok(Backend::Ntfy(Self::parse_ntfy(map)?)),
+            "slack" => Ok(Backend::Slack(Slack {
+                url: Self::parse_secret("SLACK_WEBHOOK_URL", map)?,
+            })),
+            "telegram" => Ok(Backend::Telegram(Self::parse_telegram(map)?)),
+            "webhook" => Ok(Backend::Webhook(Self::parse_webhook(map)?)),
+            _ => Err(AppError::InvalidEnv(key.into())),
+        }
+    }
+
+    /// Parse each named backend, a backend can only be used once, as they each only have a single set of envs
+    fn parse_backends(
+        key: &str,
+        names: Vec<String>,
+        existing: &[Backend],
+        map: &EnvHashMap,
+    ) -> Result<Vec<Backend>, AppError> {
+        let mut notifiers: Vec<Backend> = vec![];
+        for name in names {
+            let notifier = Self::parse_notifier(key, &name, map)?;
+            if notifiers
+                .iter()
+                .chain(existing)
+                .any(|i| i.name() == notifier.name())
+            {
+                return Err(AppError::InvalidEnv(key.into()));
+            }
+            notifiers.push(notifier);
+        }
+        Ok(notifiers)
+    }
+
+    /// Get the notifier backends from the comma separated `NOTIFIER` env, defaults to just Pushover when not set
+    fn parse_notifiers(map: &EnvHashMap) -> Result<Vec<Backend>, AppError> {
+        let mut names = Self::parse_list("NOTIFIER", map);
+        if names.is_empty() {
+            names.push(S!("pushover"));
+        }
+        Self::parse_backends("NOTIFIER", names, &[], map)
+    }
+
+    /// Get the ordered fallback backends from the comma separated `NOTIFIER_FALLBACK` env, empty when not set
+    /// A fallback can't also be one of the notifiers
+    fn parse_fallbacks(map: &EnvHashMap, notifiers: &[Backend]) -> Result<Vec<Backend>, AppError> {
+        Self::parse_backends(
+            "NOTIFIER_FALLBACK",
+            Self::parse_list("NOTIFIER_FALLBACK", map),
+            notifiers,
+            map,
+        )
+    }
+
     /// Load, and parse .env file, return AppEnv
     fn generate() -> Result<Self, AppError> {
         let env_map = env::vars()
@@ -106,15 +558,23 @@ impl AppEnv {
             .collect::<HashMap<String, String>>();
 
         let base = Self::get_location()?;
+        let notifiers = Self::parse_notifiers(&env_map)?;
         Ok(Self {
             location_lock: Self::location_lock(&base),
             location_sqlite: Self::location_database(&base),
             location_base: base,
             log_level: Self::parse_log(&env_map),
             timezone: Self::parse_timezone(&env_map),
-            token_app: Self::parse_string("TOKEN_APP", &env_map)?,
-            token_user: Self::parse_string("TOKEN_USER", &env_map)?,
+            fallbacks: Self::parse_fallbacks(&env_map, &notifiers)?,
+            notifiers,
             machine_name: Self::parse_string("MACHINE_NAME", &env_map)?,
+            rate_limit: Self::parse_rate_limit(&env_map)?,
+            boot_loop: Self::parse_boot_loop(&env_map)?,
+            heartbeat: Self::parse_number("HEARTBEAT_INTERVAL", &env_map, 60 * 60 * 24)?,
+            ip_check: Self::parse_number("IP_CHECK_INTERVAL", &env_map, 60 * 5)?,
+            interface_check: Self::parse_number("INTERFACE_CHECK_INTERVAL", &env_map, 60)?,
+            interface_filter: Self::parse_interface_filter(&env_map)?,
+            ip_providers: Self::parse_ip_providers(&env_map)?,
         })
     }
 
@@ -305,6 +765,777 @@ mod tests {
         assert_eq!(result, tracing::Level::TRACE);
     }
 
+    #[test]
+    fn env_parse_notifier_pushover() {
+        let map = HashMap::from([(S!("TOKEN_APP"), S!("app")), (S!("TOKEN_USER"), S!("user"))]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Pushover(pushover) = result else {
+            unreachable!()
+        };
+        assert_eq!(pushover.url, PUSHOVER_URL);
+        assert_eq!(pushover.token_app.expose(), "app");
+        assert_eq!(pushover.token_user.expose(), "user");
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("PushOver")),
+            (S!("PUSHOVER_URL"), S!("http://127.0.0.1:8080")),
+            (S!("TOKEN_APP"), S!("app")),
+            (S!("TOKEN_USER"), S!("user")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+
+        assert!(result.is_ok());
+        let Backend::Pushover(pushover) = result.unwrap().remove(0) else {
+            unreachable!()
+        };
+        assert_eq!(pushover.url, "http://127.0.0.1:8080");
+    }
+
+    #[test]
+    fn env_parse_pushover_options() {
+        let map = HashMap::from([(S!("TOKEN_APP"), S!("app")), (S!("TOKEN_USER"), S!("user"))]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Pushover(pushover) = result else {
+            unreachable!()
+        };
+        assert_eq!(pushover.online.priority, 0);
+        assert_eq!(pushover.online.retry, 60);
+        assert_eq!(pushover.online.expire, 3600);
+        assert_eq!(pushover.online.format, PushoverFormat::Plain);
+        assert!(pushover.online.title.is_none());
+        assert_eq!(pushover.online, pushover.service);
+
+        let map = HashMap::from([
+            (S!("PUSHOVER_TITLE"), S!("{machine_name}")),
+            (S!("PUSHOVER_PRIORITY"), S!("-1")),
+            (S!("PUSHOVER_SOUND"), S!("none")),
+            (S!("PUSHOVER_DEVICE"), S!("phone, tablet")),
+            (S!("PUSHOVER_URL"), S!("http://127.0.0.1:8080")),
+            (S!("PUSHOVER_LINK_TITLE"), S!("dashboard")),
+            (S!("PUSHOVER_SERVICE_LINK"), S!("https://example.com")),
+            (S!("PUSHOVER_FORMAT"), S!("HTML")),
+            (S!("PUSHOVER_TTL"), S!("600")),
+            (S!("PUSHOVER_TIMESTAMP"), S!("true")),
+            (S!("PUSHOVER_ONLINE_PRIORITY"), S!("2")),
+            (S!("PUSHOVER_ONLINE_RETRY"), S!("30")),
+            (S!("PUSHOVER_EXPIRE"), S!("10800")),
+            (S!("PUSHOVER_ONLINE_SOUND"), S!("cosmic")),
+            (S!("PUSHOVER_ONLINE_TIMESTAMP"), S!("false")),
+            (S!("PUSHOVER_SERVICE_FORMAT"), S!("monospace")),
+            (S!("PUSHOVER_SERVICE_TITLE"), S!("  ")),
+        ]);
+
+        // ACTION
+        let online = AppEnv::parse_pushover_options("ONLINE", &map).unwrap();
+        let service = AppEnv::parse_pushover_options("SERVICE", &map).unwrap();
+
+        assert_eq!(
+            online,
+            PushoverOptions {
+                title: Some(S!("{machine_name}")),
+                priority: 2,
+                retry: 30,
+                expire: 10800,
+                sound: Some(S!("cosmic")),
+                devices: vec![S!("phone"), S!("tablet")],
+                url: None,
+                url_title: Some(S!("dashboard")),
+                format: PushoverFormat::Html,
+                ttl: Some(600),
+                timestamp: false,
+            }
+        );
+        assert_eq!(
+            service,
+            PushoverOptions {
+                title: Some(S!("{machine_name}")),
+                priority: -1,
+                retry: 60,
+                expire: 10800,
+                sound: Some(S!("none")),
+                devices: vec![S!("phone"), S!("tablet")],
+                url: Some(S!("https://example.com")),
+                url_title: Some(S!("dashboard")),
+                format: PushoverFormat::Monospace,
+                ttl: Some(600),
+                timestamp: true,
+            }
+        );
+
+        for (key, value) in [
+            ("PUSHOVER_ONLINE_PRIORITY", "3"),
+            ("PUSHOVER_ONLINE_RETRY", "29"),
+            ("PUSHOVER_ONLINE_EXPIRE", "10801"),
+            ("PUSHOVER_ONLINE_FORMAT", "markdown"),
+            ("PUSHOVER_ONLINE_TTL", "0"),
+        ] {
+            let map = HashMap::from([(S!(key), S!(value))]);
+
+            // ACTION
+            let result = AppEnv::parse_pushover_options("ONLINE", &map);
+
+            assert!(result.is_err());
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                format!("invalid env: '{key}'")
+            );
+        }
+    }
+
+    #[test]
+    fn env_parse_optional_list_number() {
+        let map = HashMap::from([
+            (S!("EMPTY"), S!("  ")),
+            (S!("LIST"), S!("a, b,,c ")),
+            (S!("NUMBER"), S!("12")),
+            (S!("NOT_NUMBER"), S!("twelve")),
+        ]);
+
+        // ACTION
+        assert!(AppEnv::parse_optional("EMPTY", &map).is_none());
+        assert!(AppEnv::parse_optional("MISSING", &map).is_none());
+        assert_eq!(AppEnv::parse_optional("NUMBER", &map), Some(S!("12")));
+
+        assert_eq!(AppEnv::parse_list("LIST", &map), vec!["a", "b", "c"]);
+        assert!(AppEnv::parse_list("MISSING", &map).is_empty());
+
+        assert_eq!(AppEnv::parse_number("NUMBER", &map, 3u8).unwrap(), 12);
+        assert_eq!(AppEnv::parse_number("MISSING", &map, 3u8).unwrap(), 3);
+        let result = AppEnv::parse_number("NOT_NUMBER", &map, 3u8);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'NOT_NUMBER'");
+    }
+
+    #[test]
+    fn env_parse_rate_limit() {
+        let result = AppEnv::parse_rate_limit(&HashMap::new()).unwrap();
+        assert_eq!(result, RateLimit::default());
+
+        let map = HashMap::from([
+            (S!("RATE_LIMIT_MAX"), S!("2")),
+            (S!("RATE_LIMIT_SERVICE_MAX"), S!("10")),
+            (S!("RATE_LIMIT_WINDOW"), S!("600")),
+        ]);
+        let result = AppEnv::parse_rate_limit(&map).unwrap();
+        assert_eq!(
+            result,
+            RateLimit {
+                max: 2,
+                max_service: 10,
+                window: 600,
+            }
+        );
+
+        let map = HashMap::from([(S!("RATE_LIMIT_WINDOW"), S!("-1"))]);
+        let result = AppEnv::parse_rate_limit(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'RATE_LIMIT_WINDOW'"
+        );
+    }
+
+    #[test]
+    fn env_parse_boot_loop() {
+        let result = AppEnv::parse_boot_loop(&HashMap::new()).unwrap();
+        assert_eq!(result, BootLoop::default());
+
+        let map = HashMap::from([
+            (S!("BOOT_LOOP_COUNT"), S!("0")),
+            (S!("BOOT_LOOP_WINDOW"), S!("300")),
+            (S!("BOOT_LOOP_STABLE"), S!("7200")),
+        ]);
+        let result = AppEnv::parse_boot_loop(&map).unwrap();
+        assert_eq!(
+            result,
+            BootLoop {
+                count: 0,
+                window: 300,
+                stable: 7200,
+            }
+        );
+
+        let map = HashMap::from([(S!("BOOT_LOOP_COUNT"), S!("five"))]);
+        let result = AppEnv::parse_boot_loop(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'BOOT_LOOP_COUNT'"
+        );
+    }
+
+    #[test]
+    fn env_parse_interface_filter() {
+        let result = AppEnv::parse_interface_filter(&HashMap::new()).unwrap();
+        assert_eq!(result, InterfaceFilter::default());
+
+        let map = HashMap::from([
+            (S!("INTERFACE_INCLUDE"), S!("eth*, 192.168.0.0/16")),
+            (
+                S!("INTERFACE_EXCLUDE"),
+                S!("docker0,veth*,,fd7a:115c:a1e0::/48"),
+            ),
+        ]);
+        let result = AppEnv::parse_interface_filter(&map).unwrap();
+        assert_eq!(
+            result,
+            InterfaceFilter {
+                include: vec![
+                    InterfaceRule::Name(S!("eth*")),
+                    InterfaceRule::Cidr("192.168.0.0/16".parse().unwrap()),
+                ],
+                exclude: vec![
+                    InterfaceRule::Name(S!("docker0")),
+                    InterfaceRule::Name(S!("veth*")),
+                    InterfaceRule::Cidr("fd7a:115c:a1e0::/48".parse().unwrap()),
+                ],
+            }
+        );
+
+        let map = HashMap::from([(S!("INTERFACE_EXCLUDE"), S!("docker0,172.17.0.0/33"))]);
+        let result = AppEnv::parse_interface_filter(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'INTERFACE_EXCLUDE'"
+        );
+    }
+
+    #[test]
+    fn env_parse_ip_providers() {
+        let result = AppEnv::parse_ip_providers(&HashMap::new()).unwrap();
+        assert_eq!(result, IpProviders::default());
+
+        let map = HashMap::from([
+            (
+                S!("IPV4_PROVIDERS"),
+                S!(
+                    "https://myipv4.p1.opendns.com/get_my_ip json:ip, https://api.ipify.org, https://ipv4.icanhazip.com text"
+                ),
+            ),
+            (S!("IPV6_PROVIDERS"), S!("None")),
+        ]);
+        let result = AppEnv::parse_ip_providers(&map).unwrap();
+        assert_eq!(
+            result.v4.iter().map(|i| C!(i.url)).collect::<Vec<_>>(),
+            vec![
+                "https://myipv4.p1.opendns.com/get_my_ip",
+                "https://api.ipify.org",
+                "https://ipv4.icanhazip.com"
+            ]
+        );
+        assert_eq!(result.v4[0], IpProviders::default().v4[0]);
+        assert!(result.v6.is_empty());
+
+        let map = HashMap::from([(S!("IPV6_PROVIDERS"), S!("https://api6.ipify.org xml"))]);
+        let result = AppEnv::parse_ip_providers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'IPV6_PROVIDERS'"
+        );
+    }
+
+    #[test]
+    fn env_parse_notifier_ntfy() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("ntfy")),
+            (S!("NTFY_TOPIC"), S!("topic")),
+            (S!("NTFY_USERNAME"), S!("user")),
+            (S!("NTFY_PASSWORD"), S!("pass")),
+            (S!("NTFY_TAGS"), S!("computer,warning")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Ntfy(ntfy) = result else {
+            unreachable!()
+        };
+        assert_eq!(ntfy.url, NTFY_URL);
+        assert_eq!(ntfy.topic, "topic");
+        assert_eq!(ntfy.priority, 3);
+        assert_eq!(ntfy.tags, vec!["computer", "warning"]);
+        assert!(matches!(ntfy.auth, Some(NtfyAuth::Basic { .. })));
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("ntfy")),
+            (S!("NTFY_TOPIC"), S!("topic")),
+            (S!("NTFY_PRIORITY"), S!("6")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'NTFY_PRIORITY'"
+        );
+
+        let map = HashMap::from([(S!("NOTIFIER"), S!("ntfy"))]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "missing env: 'NTFY_TOPIC'");
+    }
+
+    #[test]
+    fn env_parse_notifier_gotify() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("gotify")),
+            (S!("GOTIFY_URL"), S!("https://gotify.example.com")),
+            (S!("GOTIFY_TOKEN"), S!("token")),
+            (S!("GOTIFY_MARKDOWN"), S!("true")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Gotify(gotify) = result else {
+            unreachable!()
+        };
+        assert_eq!(gotify.url, "https://gotify.example.com");
+        assert_eq!(gotify.token.expose(), "token");
+        assert!(gotify.title.is_none());
+        assert_eq!(gotify.priority, 5);
+        assert!(gotify.markdown);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("gotify")),
+            (S!("GOTIFY_URL"), S!("https://gotify.example.com")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing env: 'GOTIFY_TOKEN'"
+        );
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("gotify")),
+            (S!("GOTIFY_URL"), S!("https://gotify.example.com")),
+            (S!("GOTIFY_TOKEN"), S!("token")),
+            (S!("GOTIFY_PRIORITY"), S!("11")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'GOTIFY_PRIORITY'"
+        );
+    }
+
+    #[test]
+    fn env_parse_notifier_telegram() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("telegram")),
+            (S!("TELEGRAM_TOKEN"), S!("123:token")),
+            (S!("TELEGRAM_CHAT_ID"), S!("-100123")),
+            (S!("TELEGRAM_THREAD_ID"), S!("42")),
+            (S!("TELEGRAM_PARSE_MODE"), S!("MarkdownV2")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Telegram(telegram) = result else {
+            unreachable!()
+        };
+        assert_eq!(telegram.url, TELEGRAM_URL);
+        assert_eq!(telegram.token.expose(), "123:token");
+        assert_eq!(telegram.chat_id, "-100123");
+        assert_eq!(telegram.thread_id, Some(42));
+        assert_eq!(telegram.parse_mode, ParseMode::MarkdownV2);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("telegram")),
+            (S!("TELEGRAM_TOKEN"), S!("123:token")),
+            (S!("TELEGRAM_CHAT_ID"), S!("-100123")),
+            (S!("TELEGRAM_PARSE_MODE"), S!("bbcode")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'TELEGRAM_PARSE_MODE'"
+        );
+    }
+
+    #[test]
+    fn env_parse_notifier_webhooks() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("discord")),
+            (
+                S!("DISCORD_WEBHOOK_URL"),
+                S!("https://discord.com/api/webhooks/1/a"),
+            ),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+        assert!(
+            matches!(result, Backend::Discord(discord) if discord.url.expose() == "https://discord.com/api/webhooks/1/a")
+        );
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("slack")),
+            (
+                S!("SLACK_WEBHOOK_URL"),
+                S!("https://hooks.slack.com/services/a"),
+            ),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+        assert!(
+            matches!(result, Backend::Slack(slack) if slack.url.expose() == "https://hooks.slack.com/services/a")
+        );
+
+        let map = HashMap::from([(S!("NOTIFIER"), S!("slack"))]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing env: 'SLACK_WEBHOOK_URL'"
+        );
+    }
+
+    #[test]
+    fn env_parse_notifier_webhook() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("webhook")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Webhook(webhook) = result else {
+            unreachable!()
+        };
+        assert_eq!(webhook.url.expose(), "https://example.com/hook");
+        assert_eq!(webhook.method, reqwest::Method::POST);
+        assert!(webhook.headers.is_empty());
+        assert_eq!(webhook.body, WEBHOOK_BODY);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("webhook")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+            (S!("WEBHOOK_METHOD"), S!("put")),
+            (
+                S!("WEBHOOK_HEADERS"),
+                S!("Authorization: Bearer abc; X-Source:sysup;"),
+            ),
+            (S!("WEBHOOK_BODY"), S!("{machine_name} {event}")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Webhook(webhook) = result else {
+            unreachable!()
+        };
+        assert_eq!(webhook.method, reqwest::Method::PUT);
+        assert_eq!(
+            webhook.headers,
+            vec![
+                (S!("Authorization"), Secret::from("Bearer abc")),
+                (S!("X-Source"), Secret::from("sysup"))
+            ]
+        );
+        assert_eq!(webhook.body, "{machine_name} {event}");
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("webhook")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+            (S!("WEBHOOK_HEADERS"), S!("Authorization")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "invalid env: 'WEBHOOK_HEADERS'"
+        );
+    }
+
+    #[test]
+    fn env_parse_notifier_email() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("email")),
+            (S!("SMTP_HOST"), S!("mail.example.com")),
+            (S!("SMTP_FROM"), S!("sysup <sysup@example.com>")),
+            (S!("SMTP_TO"), S!("ops@example.com, admin@example.com")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Email(email) = result else {
+            unreachable!()
+        };
+        assert_eq!(email.host, "mail.example.com");
+        assert_eq!(email.port, 587);
+        assert_eq!(email.security, SmtpSecurity::StartTls);
+        assert!(email.credentials.is_none());
+        assert_eq!(email.from.email.to_string(), "sysup@example.com");
+        assert_eq!(email.to.len(), 2);
+        assert_eq!(email.subject, SMTP_SUBJECT);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("email")),
+            (S!("SMTP_HOST"), S!("mail.example.com")),
+            (S!("SMTP_SECURITY"), S!("TLS")),
+            (S!("SMTP_USERNAME"), S!("user")),
+            (S!("SMTP_PASSWORD"), S!("pass")),
+            (S!("SMTP_FROM"), S!("sysup@example.com")),
+            (S!("SMTP_TO"), S!("ops@example.com")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Email(email) = result else {
+            unreachable!()
+        };
+        assert_eq!(email.port, 465);
+        assert_eq!(email.security, SmtpSecurity::Tls);
+        assert_eq!(email.credentials, Some((S!("user"), Secret::from("pass"))));
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("email")),
+            (S!("SMTP_HOST"), S!("mail.example.com")),
+            (S!("SMTP_FROM"), S!("sysup@example.com")),
+            (S!("SMTP_TO"), S!("not an address")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'SMTP_TO'");
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("email")),
+            (S!("SMTP_HOST"), S!("mail.example.com")),
+            (S!("SMTP_FROM"), S!("sysup@example.com")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "missing env: 'SMTP_TO'");
+    }
+
+    #[test]
+    fn env_parse_notifier_mqtt() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("mqtt")),
+            (S!("MQTT_HOST"), S!("broker.local")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Mqtt(mqtt) = result else {
+            unreachable!()
+        };
+        assert_eq!(mqtt.host, "broker.local");
+        assert_eq!(mqtt.port, 1883);
+        assert!(!mqtt.tls);
+        assert!(mqtt.credentials.is_none());
+        assert_eq!(mqtt.topic, MQTT_TOPIC);
+        assert_eq!(mqtt.qos, rumqttc::QoS::AtLeastOnce);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("mqtt")),
+            (S!("MQTT_HOST"), S!("broker.local")),
+            (S!("MQTT_TLS"), S!("true")),
+            (S!("MQTT_QOS"), S!("2")),
+            (S!("MQTT_TOPIC"), S!("home/machines")),
+            (S!("MQTT_USERNAME"), S!("user")),
+            (S!("MQTT_PASSWORD"), S!("pass")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap().remove(0);
+
+        let Backend::Mqtt(mqtt) = result else {
+            unreachable!()
+        };
+        assert_eq!(mqtt.port, 8883);
+        assert!(mqtt.tls);
+        assert_eq!(mqtt.credentials, Some((S!("user"), Secret::from("pass"))));
+        assert_eq!(mqtt.topic, "home/machines");
+        assert_eq!(mqtt.qos, rumqttc::QoS::ExactlyOnce);
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("mqtt")),
+            (S!("MQTT_HOST"), S!("broker.local")),
+            (S!("MQTT_QOS"), S!("3")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'MQTT_QOS'");
+    }
+
+    #[test]
+    fn env_parse_notifier_mqtt_topic_levels() {
+        for machine_name in ["rack/pi", "pi+", "pi#"] {
+            let map = HashMap::from([
+                (S!("NOTIFIER"), S!("mqtt")),
+                (S!("MQTT_HOST"), S!("broker.local")),
+                (S!("MACHINE_NAME"), S!(machine_name)),
+            ]);
+
+            // ACTION
+            let result = AppEnv::parse_notifiers(&map);
+            assert!(result.is_err());
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "invalid env: 'MACHINE_NAME'"
+            );
+        }
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("mqtt")),
+            (S!("MQTT_HOST"), S!("broker.local")),
+            (S!("MQTT_TOPIC"), S!("home/+")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'MQTT_TOPIC'");
+
+        // Only mqtt puts the machine name in a topic
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("ntfy")),
+            (S!("NTFY_TOPIC"), S!("topic")),
+            (S!("MACHINE_NAME"), S!("rack/pi")),
+        ]);
+
+        // ACTION
+        assert!(AppEnv::parse_notifiers(&map).is_ok());
+    }
+
+    #[test]
+    fn env_parse_notifier_multiple() {
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("pushover, webhook")),
+            (S!("TOKEN_APP"), S!("app")),
+            (S!("TOKEN_USER"), S!("user")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map).unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].name(), "pushover");
+        assert_eq!(result[1].name(), "webhook");
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("webhook,pushover,webhook")),
+            (S!("TOKEN_APP"), S!("app")),
+            (S!("TOKEN_USER"), S!("user")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'NOTIFIER'");
+    }
+
+    #[test]
+    fn env_parse_notifier_err() {
+        let map = HashMap::from([(S!("TOKEN_APP"), S!("app"))]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "missing env: 'TOKEN_USER'");
+
+        let map = HashMap::from([
+            (S!("NOTIFIER"), S!("carrier_pigeon")),
+            (S!("TOKEN_APP"), S!("app")),
+            (S!("TOKEN_USER"), S!("user")),
+        ]);
+
+        // ACTION
+        let result = AppEnv::parse_notifiers(&map);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().to_string(), "invalid env: 'NOTIFIER'");
+    }
+
+    #[test]
+    fn env_parse_fallback() {
+        let map = HashMap::from([
+            (S!("TOKEN_APP"), S!("app")),
+            (S!("TOKEN_USER"), S!("user")),
+            (S!("WEBHOOK_URL"), S!("https://example.com/hook")),
+            (S!("DISCORD_WEBHOOK_URL"), S!("https://example.com/discord")),
+        ]);
+        let notifiers = AppEnv::parse_notifiers(&map).unwrap();
+
+        // ACTION
+        let result = AppEnv::parse_fallbacks(&map, &notifiers).unwrap();
+        assert!(result.is_empty());
+
+        let mut map = map;
+        map.insert(S!("NOTIFIER_FALLBACK"), S!("webhook, discord"));
+
+        // ACTION
+        let result = AppEnv::parse_fallbacks(&map, &notifiers).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].name(), "webhook");
+        assert_eq!(result[1].name(), "discord");
+
+        for fallback in ["webhook,webhook", "pushover", "carrier_pigeon"] {
+            map.insert(S!("NOTIFIER_FALLBACK"), S!(fallback));
+
+            // ACTION
+            let result = AppEnv::parse_fallbacks(&map, &notifiers);
+            assert!(result.is_err());
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "invalid env: 'NOTIFIER_FALLBACK'"
+            );
+        }
+
+        map.insert(S!("NOTIFIER_FALLBACK"), S!("slack"));
+
+        // ACTION
+        let result = AppEnv::parse_fallbacks(&map, &notifiers);
+        assert!(result.is_err());
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "missing env: 'SLACK_WEBHOOK_URL'"
+        );
+    }
+
     // #[test]
     // fn env_panic_appenv() {
     //     // ACTION
@@ -11,14 +11,36 @@ pub enum AppError {
     Convert(#[from] TryFromIntError),
     #[error("IO Error")]
     IOError(#[from] std::io::Error),
+    #[error("Email error: {0}")]
+    Email(#[from] lettre::error::Error),
+    #[error("invalid env: '{0}'")]
+    InvalidEnv(String),
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
     #[error("missing env: '{0}'")]
     MissingEnv(String),
+    #[error("Mqtt client error: {0}")]
+    MqttClient(#[from] rumqttc::ClientError),
+    #[error("Mqtt connection error: {0}")]
+    MqttConnection(Box<rumqttc::ConnectionError>),
+    #[error("Pushover error {0}: {1}")]
+    Pushover(u16, String),
+    #[error("Pushover limit reached, {0} remaining, resets at {1}")]
+    PushoverLimit(u64, u64),
     #[error("Reqwest Error")]
     Reqwest(#[from] reqwest::Error),
+    #[error("Smtp error: {0}")]
+    Smtp(#[from] lettre::transport::smtp::Error),
     #[error("Internal Database Error: {0}")]
     Sqlx(#[from] sqlx::Error),
+    #[error("Telegram error {0}: {1}")]
+    Telegram(i64, String),
+    #[error("Timeout: {0}")]
+    Timeout(#[from] tokio::time::error::Elapsed),
     #[error("Unable to set up tracing")]
     Tracing,
+    #[error("No notifier was able to deliver the message")]
+    Undelivered,
     #[error("Url parsing error: {0}")]
     Url(#[from] url::ParseError),
 }
@@ -0,0 +1,130 @@
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use tokio::{
+    sync::watch,
+    time::{Instant, Interval, MissedTickBehavior, interval_at},
+};
+
+use crate::{app_env::AppEnv, db::ModelSkipRequest, interface::Interface, request::PushRequest};
+
+/// Time between each retry of the outbox, report of any suppressed alerts, and check of the pushover receipts
+const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An interval which first ticks after one period, rather than straight away
+fn gen_interval(period: Duration) -> Interval {
+    let mut interval = interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+/// Keep running after the online message, sending a heartbeat every `HEARTBEAT_INTERVAL` seconds, checking the public ips every `IP_CHECK_INTERVAL` seconds, and the network interfaces every `INTERFACE_CHECK_INTERVAL` seconds, and retrying the outbox, reporting suppressed alerts, & checking receipts, until a shutdown signal is received
+pub async fn run(app_env: &AppEnv, db: &SqlitePool, mut shutdown: watch::Receiver<bool>) {
+    // A period of 0 would panic, the heartbeat branch is disabled instead
+    let mut heartbeat = gen_interval(Duration::from_secs(app_env.heartbeat.max(1)));
+    let mut ip_check = gen_interval(Duration::from_secs(app_env.ip_check.max(1)));
+    let mut interface_check = gen_interval(Duration::from_secs(app_env.interface_check.max(1)));
+    let mut checks = gen_interval(CHECK_INTERVAL);
+    tracing::info!("Daemon running");
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            _ = heartbeat.tick(), if app_env.heartbeat > 0 => {
+                if ModelSkipRequest::get(db).await.is_some_and(|i| !i.skip)
+                    && let Err(e) = PushRequest::Heartbeat.make_request(app_env, db).await
+                {
+                    tracing::error!("heartbeat: {e}");
+                }
+            }
+            _ = ip_check.tick(), if app_env.ip_check > 0 => {
+                if ModelSkipRequest::get(db).await.is_some_and(|i| !i.skip)
+                    && let Err(e) = PushRequest::check_public_ip(app_env, db).await
+                {
+                    tracing::error!("ip check: {e}");
+                }
+            }
+            _ = interface_check.tick(), if app_env.interface_check > 0 => {
+                if let Err(e) =
+                    PushRequest::check_interfaces(app_env, db, Interface::get_all(&app_env.interface_filter)).await
+                {
+                    tracing::error!("interfaces: {e}");
+                }
+            }
+            _ = checks.tick() => {
+                if let Err(e) = PushRequest::retry_outbox(app_env, db).await {
+                    tracing::error!("outbox: {e}");
+                }
+                if let Err(e) = PushRequest::report_suppressed(app_env, db).await {
+                    tracing::error!("suppressed: {e}");
+                }
+                if let Err(e) = PushRequest::check_receipts(app_env, db).await {
+                    tracing::error!("receipts: {e}");
+                }
+            }
+        }
+    }
+    tracing::info!("Daemon shutting down");
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        notifier::Backend,
+        tests::{StandIn, gen_webhook, setup_test, test_cleanup},
+    };
+
+    #[tokio::test]
+    // A heartbeat is sent each interval, and the daemon stops once told to
+    async fn test_daemon_heartbeat_shutdown() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.heartbeat = 1;
+        ModelSkipRequest::update(&db, false).await.unwrap();
+        let (tx, rx) = watch::channel(false);
+
+        let daemon = {
+            let (app_env, db) = (app_env.clone(), db.clone());
+            tokio::spawn(async move { run(&app_env, &db, rx).await })
+        };
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), daemon)
+            .await
+            .unwrap()
+            .unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body.contains(r#""event":"heartbeat""#));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Nothing sent when the heartbeat is disabled
+    async fn test_daemon_heartbeat_disabled() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.heartbeat = 0;
+        ModelSkipRequest::update(&db, false).await.unwrap();
+        let (tx, rx) = watch::channel(false);
+
+        let daemon = {
+            let (app_env, db) = (app_env.clone(), db.clone());
+            tokio::spawn(async move { run(&app_env, &db, rx).await })
+        };
+        tokio::time::sleep(Duration::from_millis(1500)).await;
+        tx.send(true).unwrap();
+        tokio::time::timeout(Duration::from_secs(1), daemon)
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(ok.requests().is_empty());
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
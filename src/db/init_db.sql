@@ -1,7 +1,12 @@
 BEGIN;
 	CREATE TABLE IF NOT EXISTS request  (
 	request_id INTEGER PRIMARY KEY AUTOINCREMENT,
-	timestamp INTEGER NOT NULL
+	timestamp INTEGER NOT NULL,
+	delivered INTEGER NOT NULL DEFAULT 0 CHECK (delivered IN (0, 1)),
+	pushover_request TEXT,
+	event TEXT,
+	suppressed INTEGER NOT NULL DEFAULT 0 CHECK (suppressed IN (0, 1)),
+	muted INTEGER NOT NULL DEFAULT 0 CHECK (muted IN (0, 1))
 ) STRICT;
 
 -- Not needed
@@ -15,4 +20,84 @@ CREATE TABLE IF NOT EXISTS skip_request  (
 	skip INTEGER NOT NULL CHECK (skip IN (0, 1))
 ) STRICT;
 
+-- Each attempt to deliver a request, via either a notifier or a fallback
+CREATE TABLE IF NOT EXISTS delivery  (
+	delivery_id INTEGER PRIMARY KEY AUTOINCREMENT,
+	request_id INTEGER NOT NULL REFERENCES request(request_id),
+	timestamp INTEGER NOT NULL,
+	notifier TEXT NOT NULL,
+	fallback INTEGER NOT NULL CHECK (fallback IN (0, 1)),
+	delivered INTEGER NOT NULL CHECK (delivered IN (0, 1)),
+	error TEXT
+) STRICT;
+
+-- Messages that no notifier was able to deliver, retried with an exponential backoff
+CREATE TABLE IF NOT EXISTS outbox  (
+	outbox_id INTEGER PRIMARY KEY AUTOINCREMENT,
+	request_id INTEGER NOT NULL REFERENCES request(request_id),
+	message TEXT NOT NULL,
+	created INTEGER NOT NULL,
+	attempts INTEGER NOT NULL,
+	next_attempt INTEGER NOT NULL,
+	delivered INTEGER NOT NULL CHECK (delivered IN (0, 1))
+) STRICT;
+
+-- Pushover emergency priority receipts, polled until acknowledged or expired
+CREATE TABLE IF NOT EXISTS receipt  (
+	receipt_id INTEGER PRIMARY KEY AUTOINCREMENT,
+	request_id INTEGER NOT NULL REFERENCES request(request_id),
+	receipt TEXT NOT NULL UNIQUE,
+	created INTEGER NOT NULL,
+	last_checked INTEGER,
+	acknowledged INTEGER NOT NULL CHECK (acknowledged IN (0, 1)),
+	acknowledged_at INTEGER,
+	acknowledged_by TEXT,
+	acknowledged_by_device TEXT,
+	expired INTEGER NOT NULL CHECK (expired IN (0, 1))
+) STRICT;
+
+-- Only exists while the machine is boot looping
+CREATE TABLE IF NOT EXISTS boot_loop  (
+	boot_loop_id INTEGER PRIMARY KEY AUTOINCREMENT CHECK (boot_loop_id = 1),
+	detected INTEGER NOT NULL,
+	boots INTEGER NOT NULL
+) STRICT;
+
+-- Written by the shutdown hook, a boot without one since the previous boot means a crash, or loss of power
+CREATE TABLE IF NOT EXISTS shutdown  (
+	shutdown_id INTEGER PRIMARY KEY AUTOINCREMENT,
+	timestamp INTEGER NOT NULL,
+	reason TEXT
+) STRICT;
+
+-- Each boot, keyed by the linux boot id, so the same boot is never notified twice
+CREATE TABLE IF NOT EXISTS boot  (
+	boot_id TEXT PRIMARY KEY,
+	booted INTEGER NOT NULL,
+	kernel TEXT,
+	hostname TEXT,
+	local_ip TEXT,
+	ipv4 TEXT,
+	ipv6 TEXT,
+	request_id INTEGER REFERENCES request(request_id),
+	notified INTEGER NOT NULL CHECK (notified IN (0, 1))
+) STRICT;
+
+-- The last known public ips, checked periodically in daemon mode
+CREATE TABLE IF NOT EXISTS public_ip  (
+	public_ip_id INTEGER PRIMARY KEY AUTOINCREMENT CHECK (public_ip_id = 1),
+	ipv4 TEXT,
+	ipv6 TEXT,
+	updated INTEGER NOT NULL
+) STRICT;
+
+-- The last snapshot of the network interfaces, addresses are space separated
+CREATE TABLE IF NOT EXISTS interface  (
+	name TEXT PRIMARY KEY,
+	mac TEXT,
+	state TEXT,
+	addresses TEXT NOT NULL,
+	updated INTEGER NOT NULL
+) STRICT;
+
 COMMIT;
\ No newline at end of file
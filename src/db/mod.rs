@@ -1,10 +1,26 @@
+mod model_boot;
+mod model_boot_loop;
+mod model_delivery;
+mod model_interface;
+mod model_outbox;
+mod model_public_ip;
+mod model_receipt;
 mod model_request;
+mod model_shutdown;
 mod model_skip_request;
 
+pub use model_boot::ModelBoot;
+pub use model_boot_loop::ModelBootLoop;
+pub use model_delivery::ModelDelivery;
+pub use model_interface::ModelInterface;
+pub use model_outbox::ModelOutbox;
+pub use model_public_ip::ModelPublicIp;
+pub use model_receipt::ModelReceipt;
 pub use model_request::ModelRequest;
+pub use model_shutdown::ModelShutdown;
 pub use model_skip_request::ModelSkipRequest;
 
-use sqlx::{ConnectOptions, SqlitePool, sqlite::SqliteJournalMode};
+use sqlx::{AssertSqlSafe, ConnectOptions, SqlitePool, sqlite::SqliteJournalMode};
 
 use crate::{Code, app_env::AppEnv, exit};
 
@@ -46,10 +62,57 @@ async fn create_tables(db: &SqlitePool) {
     }
 }
 
+/// Columns added after a table was first created, `CREATE TABLE IF NOT EXISTS` won't add them to an existing database
+/// (table, column, definition)
+const MIGRATIONS: [(&str, &str, &str); 5] = [
+    (
+        "request",
+        "delivered",
+        "INTEGER NOT NULL DEFAULT 0 CHECK (delivered IN (0, 1))",
+    ),
+    ("request", "pushover_request", "TEXT"),
+    ("request", "event", "TEXT"),
+    (
+        "request",
+        "suppressed",
+        "INTEGER NOT NULL DEFAULT 0 CHECK (suppressed IN (0, 1))",
+    ),
+    (
+        "request",
+        "muted",
+        "INTEGER NOT NULL DEFAULT 0 CHECK (muted IN (0, 1))",
+    ),
+];
+
+/// Add any column from MIGRATIONS that the table is missing
+async fn migrate(db: &SqlitePool) {
+    for (table, column, definition) in MIGRATIONS {
+        let sql = "SELECT COUNT(*) FROM pragma_table_info($1) WHERE name = $2";
+        let exists = sqlx::query_scalar::<_, i64>(sql)
+            .bind(table)
+            .bind(column)
+            .fetch_one(db)
+            .await
+            .is_ok_and(|i| i > 0);
+        if !exists
+            // Only ever built from the MIGRATIONS const
+            && let Err(e) = sqlx::query(AssertSqlSafe(format!(
+                "ALTER TABLE {table} ADD COLUMN {column} {definition}"
+            )))
+            .execute(db)
+            .await
+        {
+            let err = format!("migrate::{table}::{column}::{e}");
+            exit(&err, &Code::Invalid);
+        }
+    }
+}
+
 /// Init db connection, works if folder/files exists or not
 pub async fn init_db(app_env: &AppEnv) -> Result<SqlitePool, sqlx::Error> {
     let db = get_db(app_env).await?;
     create_tables(&db).await;
+    migrate(&db).await;
     insert_skip_request(&db).await;
     Ok(db)
 }
@@ -135,4 +198,40 @@ mod tests {
         db.close().await;
         test_cleanup(uuid, None).await;
     }
+
+    #[tokio::test]
+    // A database created before the request columns existed is migrated
+    async fn sql_mod_db_migrated() {
+        let uuid = Uuid::new_v4();
+        let args = gen_app_env(uuid);
+        let db = get_db(&args).await.unwrap();
+        sqlx::query("CREATE TABLE request (request_id INTEGER PRIMARY KEY AUTOINCREMENT, timestamp INTEGER NOT NULL) STRICT")
+            .execute(&db)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO request(timestamp) VALUES (1)")
+            .execute(&db)
+            .await
+            .unwrap();
+        db.close().await;
+
+        // ACTION
+        let db = init_db(&args).await.unwrap();
+
+        let result = ModelRequest::get_all(&db).await;
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.len(), 1);
+        assert!(!result[0].delivered);
+        assert!(result[0].pushover_request.is_none());
+        assert!(result[0].event.is_none());
+        assert!(!result[0].suppressed);
+
+        // Running again is a no-op
+        migrate(&db).await;
+        assert_eq!(ModelRequest::get_all(&db).await.unwrap().len(), 1);
+
+        // CLEANUP
+        test_cleanup(uuid, Some(db)).await;
+    }
 }
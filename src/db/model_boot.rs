@@ -0,0 +1,185 @@
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{fmt, net::IpAddr};
+
+use crate::app_error::AppError;
+
+/// Each boot of the machine, keyed by the linux boot id, so the same boot is never notified twice
+#[derive(sqlx::FromRow, Debug, Clone, Deserialize)]
+pub struct ModelBoot {
+    pub boot_id: String,
+    /// When the machine booted, seconds since the unix epoch
+    #[sqlx(try_from = "i64")]
+    pub booted: u64,
+    pub kernel: Option<String>,
+    pub hostname: Option<String>,
+    pub local_ip: Option<String>,
+    pub ipv4: Option<String>,
+    pub ipv6: Option<String>,
+    /// The request made for this boot, None if it was suppressed
+    pub request_id: Option<i64>,
+    /// Set once a notifier, or fallback, has accepted the request
+    pub notified: bool,
+}
+
+impl fmt::Display for ModelBoot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "boot_id: {}, booted: {}, kernel: {}, hostname: {}, local_ip: {}, ipv4: {}, ipv6: {}, request_id: {}, notified: {}",
+            self.boot_id,
+            self.booted,
+            self.kernel.as_deref().unwrap_or_default(),
+            self.hostname.as_deref().unwrap_or_default(),
+            self.local_ip.as_deref().unwrap_or_default(),
+            self.ipv4.as_deref().unwrap_or_default(),
+            self.ipv6.as_deref().unwrap_or_default(),
+            self.request_id.unwrap_or_default(),
+            self.notified
+        )
+    }
+}
+
+impl ModelBoot {
+    #[cfg(test)]
+    /// In insertion order, as boots recorded within the same second can share, or swap, their booted time
+    pub async fn get_all(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM boot ORDER BY rowid";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_all(db).await?;
+        Ok(result)
+    }
+
+    /// Insert a new boot, returns None if the boot has already been recorded
+    pub async fn insert(
+        db: &SqlitePool,
+        boot_id: &str,
+        booted: u64,
+        kernel: Option<&str>,
+        hostname: Option<&str>,
+        local_ip: Option<&str>,
+    ) -> Result<Option<Self>, AppError> {
+        let sql = "INSERT INTO boot(boot_id, booted, kernel, hostname, local_ip, notified) VALUES ($1, $2, $3, $4, $5, 0) ON CONFLICT(boot_id) DO NOTHING RETURNING *";
+        let result = sqlx::query_as::<_, Self>(sql)
+            .bind(boot_id)
+            .bind(i64::try_from(booted)?)
+            .bind(kernel)
+            .bind(hostname)
+            .bind(local_ip)
+            .fetch_optional(db)
+            .await?;
+        Ok(result)
+    }
+
+    /// Link the request made for the boot, along with the public ips it found
+    pub async fn set_request(
+        db: &SqlitePool,
+        boot_id: &str,
+        request_id: i64,
+        ipv4: Option<IpAddr>,
+        ipv6: Option<IpAddr>,
+    ) -> Result<(), AppError> {
+        let sql = "UPDATE boot SET request_id = $1, ipv4 = $2, ipv6 = $3 WHERE boot_id = $4";
+        sqlx::query(sql)
+            .bind(request_id)
+            .bind(ipv4.map(|i| i.to_string()))
+            .bind(ipv6.map(|i| i.to_string()))
+            .bind(boot_id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
+    /// The request has been delivered, a no-op if the request wasn't made for a boot
+    pub async fn set_notified(db: &SqlitePool, request_id: i64) -> Result<(), AppError> {
+        let sql = "UPDATE boot SET notified = 1 WHERE request_id = $1";
+        sqlx::query(sql).bind(request_id).execute(db).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::{
+        db::ModelRequest,
+        tests::{setup_test, test_cleanup},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_boot_insert_once() {
+        let (_app_env, db, uuid) = setup_test().await;
+
+        let result = ModelBoot::insert(
+            &db,
+            "boot_a",
+            1_735_732_800,
+            Some("6.1.0"),
+            Some("host"),
+            Some("192.168.0.10"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(result.boot_id, "boot_a");
+        assert_eq!(result.booted, 1_735_732_800);
+        assert_eq!(result.kernel.as_deref(), Some("6.1.0"));
+        assert!(result.request_id.is_none());
+        assert!(!result.notified);
+
+        // Same boot
+        let result = ModelBoot::insert(&db, "boot_a", 1_735_732_900, None, None, None)
+            .await
+            .unwrap();
+        assert!(result.is_none());
+
+        ModelBoot::insert(&db, "boot_b", 1_735_733_000, None, None, None)
+            .await
+            .unwrap()
+            .unwrap();
+        let result = ModelBoot::get_all(&db).await.unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].booted, 1_735_732_800);
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    async fn model_boot_set_request_notified() {
+        let (_app_env, db, uuid) = setup_test().await;
+        ModelBoot::insert(&db, "boot_a", 1_735_732_800, None, None, None)
+            .await
+            .unwrap();
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+        let other_request = ModelRequest::insert(&db, "service installed", false)
+            .await
+            .unwrap();
+
+        ModelBoot::set_request(
+            &db,
+            "boot_a",
+            request.request_id,
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        )
+        .await
+        .unwrap();
+        ModelBoot::set_notified(&db, other_request.request_id)
+            .await
+            .unwrap();
+        let result = ModelBoot::get_all(&db).await.unwrap();
+        assert_eq!(result[0].request_id, Some(request.request_id));
+        assert_eq!(result[0].ipv4.as_deref(), Some("127.0.0.1"));
+        assert_eq!(result[0].ipv6.as_deref(), Some("::1"));
+        assert!(!result[0].notified);
+
+        ModelBoot::set_notified(&db, request.request_id)
+            .await
+            .unwrap();
+        let result = ModelBoot::get_all(&db).await.unwrap();
+        assert!(result[0].notified);
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
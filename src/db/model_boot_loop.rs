@@ -0,0 +1,83 @@
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::app_error::AppError;
+
+use super::ModelRequest;
+
+/// Only exists while the machine is boot looping, so that a single escalated alert is sent
+#[derive(sqlx::FromRow, Debug, Clone, Deserialize)]
+pub struct ModelBootLoop {
+    pub boot_loop_id: i64,
+    /// When the loop was detected, seconds since the unix epoch
+    #[sqlx(try_from = "i64")]
+    pub detected: u64,
+    /// Number of boots in the window that triggered the detection
+    pub boots: i64,
+}
+
+impl fmt::Display for ModelBootLoop {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "boot_loop_id: {}, detected: {}, boots: {}",
+            self.boot_loop_id, self.detected, self.boots
+        )
+    }
+}
+
+impl ModelBootLoop {
+    pub async fn get(db: &SqlitePool) -> Option<Self> {
+        let sql = "SELECT * FROM boot_loop";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_one(db).await;
+        result.ok()
+    }
+
+    pub async fn insert(db: &SqlitePool, boots: usize) -> Result<Self, AppError> {
+        let sql = "INSERT INTO boot_loop (detected, boots) VALUES($1, $2) RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .bind(i64::try_from(boots)?)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    /// The machine has been stable, so the loop is over
+    pub async fn delete(db: &SqlitePool) -> Result<(), AppError> {
+        let sql = "DELETE FROM boot_loop";
+        sqlx::query(sql).execute(db).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use crate::tests::{setup_test, test_cleanup};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_boot_loop_insert_delete() {
+        let (_app_env, db, uuid) = setup_test().await;
+        assert!(ModelBootLoop::get(&db).await.is_none());
+
+        let now = ModelRequest::now();
+        let result = ModelBootLoop::insert(&db, 5).await.unwrap();
+        assert_eq!(result.boot_loop_id, 1);
+        assert_eq!(result.boots, 5);
+        assert!(result.detected >= now);
+
+        // Only ever a single row
+        assert!(ModelBootLoop::insert(&db, 6).await.is_err());
+
+        let result = ModelBootLoop::get(&db).await.unwrap();
+        assert_eq!(result.boots, 5);
+
+        ModelBootLoop::delete(&db).await.unwrap();
+        assert!(ModelBootLoop::get(&db).await.is_none());
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
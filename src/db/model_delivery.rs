@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::{app_error::AppError, db::ModelRequest};
+
+#[derive(sqlx::FromRow, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelDelivery {
+    pub delivery_id: i64,
+    pub request_id: i64,
+    #[sqlx(try_from = "i64")]
+    pub timestamp: u64,
+    pub notifier: String,
+    pub fallback: bool,
+    pub delivered: bool,
+    pub error: Option<String>,
+}
+
+impl fmt::Display for ModelDelivery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "delivery_id: {}, request_id: {}, timestamp: {}, notifier: {}, fallback: {}, delivered: {}, error: {}",
+            self.delivery_id,
+            self.request_id,
+            self.timestamp,
+            self.notifier,
+            self.fallback,
+            self.delivered,
+            self.error.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+impl ModelDelivery {
+    #[cfg(test)]
+    /// Get every delivery attempt for a given request, in the order they were made
+    pub async fn get(db: &SqlitePool, request_id: i64) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM delivery WHERE request_id = $1 ORDER BY delivery_id";
+        let result = sqlx::query_as::<_, Self>(sql)
+            .bind(request_id)
+            .fetch_all(db)
+            .await?;
+        Ok(result)
+    }
+
+    /// Insert a delivery attempt, `error` is None when the notifier delivered the message
+    pub async fn insert(
+        db: &SqlitePool,
+        request_id: i64,
+        notifier: &str,
+        fallback: bool,
+        error: Option<String>,
+    ) -> Result<Self, AppError> {
+        let sql = "INSERT INTO delivery(request_id, timestamp, notifier, fallback, delivered, error) VALUES ($1, $2, $3, $4, $5, $6) RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(request_id)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .bind(notifier)
+            .bind(fallback)
+            .bind(error.is_none())
+            .bind(error)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use crate::{
+        S,
+        tests::{setup_test, test_cleanup},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_delivery_insert_get_ok() {
+        let (_app_env, db, uuid) = setup_test().await;
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+        let other_request = ModelRequest::insert(&db, "online", false).await.unwrap();
+
+        ModelDelivery::insert(&db, request.request_id, "pushover", false, Some(S!("err")))
+            .await
+            .unwrap();
+        ModelDelivery::insert(&db, other_request.request_id, "ntfy", false, None)
+            .await
+            .unwrap();
+        ModelDelivery::insert(&db, request.request_id, "email", true, None)
+            .await
+            .unwrap();
+
+        let result = ModelDelivery::get(&db, request.request_id).await;
+
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.len(), 2);
+
+        assert_eq!(result[0].notifier, "pushover");
+        assert!(!result[0].fallback);
+        assert!(!result[0].delivered);
+        assert_eq!(result[0].error, Some(S!("err")));
+
+        assert_eq!(result[1].notifier, "email");
+        assert!(result[1].fallback);
+        assert!(result[1].delivered);
+        assert!(result[1].error.is_none());
+        assert_eq!(result[1].request_id, request.request_id);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
@@ -0,0 +1,136 @@
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::{app_error::AppError, interface::Interface};
+
+use super::ModelRequest;
+
+/// The last snapshot of the network interfaces, taken at boot, and periodically in daemon mode
+#[derive(sqlx::FromRow, Debug, Clone, Deserialize)]
+pub struct ModelInterface {
+    pub name: String,
+    pub mac: Option<String>,
+    pub state: Option<String>,
+    /// Space separated, empty if the interface has no address
+    pub addresses: String,
+    /// When the snapshot was taken, seconds since the unix epoch
+    #[sqlx(try_from = "i64")]
+    pub updated: u64,
+}
+
+impl fmt::Display for ModelInterface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "name: {}, mac: {}, state: {}, addresses: {}, updated: {}",
+            self.name,
+            self.mac.as_deref().unwrap_or_default(),
+            self.state.as_deref().unwrap_or_default(),
+            self.addresses,
+            self.updated
+        )
+    }
+}
+
+impl From<ModelInterface> for Interface {
+    fn from(value: ModelInterface) -> Self {
+        Self {
+            addresses: value
+                .addresses
+                .split_whitespace()
+                .filter_map(|i| i.parse().ok())
+                .collect(),
+            name: value.name,
+            mac: value.mac,
+            state: value.state,
+        }
+    }
+}
+
+impl ModelInterface {
+    pub async fn get_all(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM interface ORDER BY name";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_all(db).await?;
+        Ok(result)
+    }
+
+    /// Replace the previous snapshot with a new one
+    pub async fn replace(db: &SqlitePool, interfaces: &[Interface]) -> Result<(), AppError> {
+        let updated = i64::try_from(ModelRequest::now())?;
+        let mut transaction = db.begin().await?;
+        sqlx::query("DELETE FROM interface")
+            .execute(&mut *transaction)
+            .await?;
+        for interface in interfaces {
+            let sql = "INSERT INTO interface(name, mac, state, addresses, updated) VALUES ($1, $2, $3, $4, $5)";
+            sqlx::query(sql)
+                .bind(&interface.name)
+                .bind(interface.mac.as_deref())
+                .bind(interface.state.as_deref())
+                .bind(
+                    interface
+                        .addresses
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                )
+                .bind(updated)
+                .execute(&mut *transaction)
+                .await?;
+        }
+        transaction.commit().await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    use crate::tests::{setup_test, test_cleanup};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_interface_replace() {
+        let (_app_env, db, uuid) = setup_test().await;
+        assert!(ModelInterface::get_all(&db).await.unwrap().is_empty());
+
+        let eth0 = Interface {
+            name: String::from("eth0"),
+            mac: Some(String::from("02:00:00:00:00:01")),
+            state: Some(String::from("up")),
+            addresses: vec![
+                IpAddr::V4(Ipv4Addr::new(192, 168, 1, 2)),
+                IpAddr::V6(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 1)),
+            ],
+        };
+        let eth1 = Interface {
+            name: String::from("eth1"),
+            mac: None,
+            state: None,
+            addresses: vec![],
+        };
+        ModelInterface::replace(&db, std::slice::from_ref(&eth0))
+            .await
+            .unwrap();
+        ModelInterface::replace(&db, &[eth1.clone(), eth0.clone()])
+            .await
+            .unwrap();
+
+        let result = ModelInterface::get_all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(Interface::from)
+            .collect::<Vec<_>>();
+        assert_eq!(result, vec![eth0, eth1]);
+
+        ModelInterface::replace(&db, &[]).await.unwrap();
+        assert!(ModelInterface::get_all(&db).await.unwrap().is_empty());
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
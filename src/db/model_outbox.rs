@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::{app_error::AppError, db::ModelRequest, notifier::Message};
+
+/// Seconds to wait before the first retry, doubled after each failed attempt
+const OUTBOX_BACKOFF: u64 = 60;
+/// Never wait longer than 6 hours between retries
+const OUTBOX_MAX_BACKOFF: u64 = 60 * 60 * 6;
+
+/// A message that failed to be delivered, retried on later runs until one of the notifiers accepts it
+#[derive(sqlx::FromRow, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelOutbox {
+    pub outbox_id: i64,
+    pub request_id: i64,
+    /// The original message, as JSON
+    pub message: String,
+    #[sqlx(try_from = "i64")]
+    pub created: u64,
+    #[sqlx(try_from = "i64")]
+    pub attempts: u64,
+    #[sqlx(try_from = "i64")]
+    pub next_attempt: u64,
+    pub delivered: bool,
+}
+
+impl fmt::Display for ModelOutbox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "outbox_id: {}, request_id: {}, created: {}, attempts: {}, next_attempt: {}, delivered: {}",
+            self.outbox_id,
+            self.request_id,
+            self.created,
+            self.attempts,
+            self.next_attempt,
+            self.delivered
+        )
+    }
+}
+
+impl ModelOutbox {
+    /// Seconds until the next attempt, after the given number of attempts have been made
+    fn backoff(attempts: u64) -> u64 {
+        u32::try_from(attempts.saturating_sub(1))
+            .ok()
+            .and_then(|i| 2u64.checked_pow(i))
+            .map_or(OUTBOX_MAX_BACKOFF, |i| {
+                OUTBOX_BACKOFF.saturating_mul(i).min(OUTBOX_MAX_BACKOFF)
+            })
+    }
+
+    /// Deserialize the stored message
+    pub fn get_message(&self) -> Result<Message, AppError> {
+        Ok(serde_json::from_str(&self.message)?)
+    }
+
+    #[cfg(test)]
+    pub async fn get_all(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM outbox ORDER BY outbox_id";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_all(db).await?;
+        Ok(result)
+    }
+
+    /// Get every undelivered message that is due a retry, oldest first
+    pub async fn get_pending(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM outbox WHERE delivered = 0 AND next_attempt <= $1 ORDER BY created, outbox_id";
+        let result = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .fetch_all(db)
+            .await?;
+        Ok(result)
+    }
+
+    /// Queue a message that has just failed its first attempt
+    pub async fn insert(
+        db: &SqlitePool,
+        request_id: i64,
+        message: &Message,
+    ) -> Result<Self, AppError> {
+        let sql = "INSERT INTO outbox(request_id, message, created, attempts, next_attempt, delivered) VALUES ($1, $2, $3, 1, $4, 0) RETURNING *";
+        let now = ModelRequest::now();
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(request_id)
+            .bind(serde_json::to_string(message)?)
+            .bind(i64::try_from(now)?)
+            .bind(i64::try_from(now + Self::backoff(1))?)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    /// Increase the attempts, and push the next attempt further into the future
+    pub async fn retry(&self, db: &SqlitePool) -> Result<Self, AppError> {
+        let sql =
+            "UPDATE outbox SET attempts = $1, next_attempt = $2 WHERE outbox_id = $3 RETURNING *";
+        let attempts = self.attempts + 1;
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(attempts)?)
+            .bind(i64::try_from(
+                ModelRequest::now() + Self::backoff(attempts),
+            )?)
+            .bind(self.outbox_id)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    pub async fn set_delivered(&self, db: &SqlitePool) -> Result<Self, AppError> {
+        let sql = "UPDATE outbox SET attempts = attempts + 1, delivered = 1 WHERE outbox_id = $1 RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(self.outbox_id)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use crate::tests::{gen_message, setup_test, test_cleanup};
+
+    use super::*;
+
+    #[test]
+    fn model_outbox_backoff() {
+        assert_eq!(ModelOutbox::backoff(1), 60);
+        assert_eq!(ModelOutbox::backoff(2), 120);
+        assert_eq!(ModelOutbox::backoff(3), 240);
+        assert_eq!(ModelOutbox::backoff(9), 15360);
+        assert_eq!(ModelOutbox::backoff(10), OUTBOX_MAX_BACKOFF);
+        assert_eq!(ModelOutbox::backoff(u64::MAX), OUTBOX_MAX_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn model_outbox_insert_retry_delivered() {
+        let (_app_env, db, uuid) = setup_test().await;
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+        let message = gen_message();
+
+        let result = ModelOutbox::insert(&db, request.request_id, &message).await;
+
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        let now = ModelRequest::now();
+        assert_eq!(result.attempts, 1);
+        assert!(!result.delivered);
+        assert!(result.next_attempt >= now + 59 && result.next_attempt <= now + 60);
+        assert_eq!(result.get_message().unwrap().text, message.text);
+
+        // Not yet due
+        assert!(ModelOutbox::get_pending(&db).await.unwrap().is_empty());
+
+        let result = result.retry(&db).await.unwrap();
+        assert_eq!(result.attempts, 2);
+        assert!(result.next_attempt >= now + 119);
+
+        sqlx::query("UPDATE outbox SET next_attempt = 0")
+            .execute(&db)
+            .await
+            .unwrap();
+        let pending = ModelOutbox::get_pending(&db).await.unwrap();
+        assert_eq!(pending.len(), 1);
+
+        let result = pending[0].set_delivered(&db).await.unwrap();
+        assert!(result.delivered);
+        assert_eq!(result.attempts, 3);
+        assert!(ModelOutbox::get_pending(&db).await.unwrap().is_empty());
+        assert_eq!(ModelOutbox::get_all(&db).await.unwrap().len(), 1);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
@@ -0,0 +1,96 @@
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::{fmt, net::IpAddr};
+
+use crate::app_error::AppError;
+
+use super::ModelRequest;
+
+/// The last known public ips, checked periodically in daemon mode
+#[derive(sqlx::FromRow, Debug, Clone, Deserialize)]
+pub struct ModelPublicIp {
+    pub public_ip_id: i64,
+    ipv4: Option<String>,
+    ipv6: Option<String>,
+    /// When either address last changed, seconds since the unix epoch
+    #[sqlx(try_from = "i64")]
+    pub updated: u64,
+}
+
+impl fmt::Display for ModelPublicIp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "public_ip_id: {}, ipv4: {}, ipv6: {}, updated: {}",
+            self.public_ip_id,
+            self.ipv4.as_deref().unwrap_or_default(),
+            self.ipv6.as_deref().unwrap_or_default(),
+            self.updated
+        )
+    }
+}
+
+impl ModelPublicIp {
+    pub fn ipv4(&self) -> Option<IpAddr> {
+        self.ipv4.as_deref().and_then(|i| i.parse().ok())
+    }
+
+    pub fn ipv6(&self) -> Option<IpAddr> {
+        self.ipv6.as_deref().and_then(|i| i.parse().ok())
+    }
+
+    pub async fn get(db: &SqlitePool) -> Result<Option<Self>, AppError> {
+        let sql = "SELECT * FROM public_ip";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_optional(db).await?;
+        Ok(result)
+    }
+
+    /// Insert, or replace, the last known addresses
+    pub async fn upsert(
+        db: &SqlitePool,
+        ipv4: Option<IpAddr>,
+        ipv6: Option<IpAddr>,
+    ) -> Result<Self, AppError> {
+        let sql = "INSERT INTO public_ip (public_ip_id, ipv4, ipv6, updated) VALUES(1, $1, $2, $3) ON CONFLICT(public_ip_id) DO UPDATE SET ipv4 = excluded.ipv4, ipv6 = excluded.ipv6, updated = excluded.updated RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(ipv4.map(|i| i.to_string()))
+            .bind(ipv6.map(|i| i.to_string()))
+            .bind(i64::try_from(ModelRequest::now())?)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::tests::{setup_test, test_cleanup};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_public_ip_upsert() {
+        let (_app_env, db, uuid) = setup_test().await;
+        assert!(ModelPublicIp::get(&db).await.unwrap().is_none());
+
+        ModelPublicIp::upsert(&db, Some(IpAddr::V4(Ipv4Addr::LOCALHOST)), None)
+            .await
+            .unwrap();
+        let result = ModelPublicIp::upsert(
+            &db,
+            Some(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.public_ip_id, 1);
+
+        let result = ModelPublicIp::get(&db).await.unwrap().unwrap();
+        assert_eq!(result.ipv4(), Some(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))));
+        assert_eq!(result.ipv6(), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::{app_error::AppError, db::ModelRequest, notifier::Receipt};
+
+/// A Pushover emergency priority receipt, checked until someone acknowledges the message, or it expires
+#[derive(sqlx::FromRow, Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModelReceipt {
+    pub receipt_id: i64,
+    pub request_id: i64,
+    pub receipt: String,
+    #[sqlx(try_from = "i64")]
+    pub created: u64,
+    pub last_checked: Option<i64>,
+    pub acknowledged: bool,
+    pub acknowledged_at: Option<i64>,
+    /// User key of whoever acknowledged it
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_by_device: Option<String>,
+    pub expired: bool,
+}
+
+impl fmt::Display for ModelReceipt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "receipt_id: {}, request_id: {}, receipt: {}, acknowledged: {}, acknowledged_at: {}, acknowledged_by: {}, acknowledged_by_device: {}, expired: {}",
+            self.receipt_id,
+            self.request_id,
+            self.receipt,
+            self.acknowledged,
+            self.acknowledged_at.unwrap_or_default(),
+            self.acknowledged_by.as_deref().unwrap_or_default(),
+            self.acknowledged_by_device.as_deref().unwrap_or_default(),
+            self.expired
+        )
+    }
+}
+
+impl ModelReceipt {
+    #[cfg(test)]
+    pub async fn get_all(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM receipt ORDER BY receipt_id";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_all(db).await?;
+        Ok(result)
+    }
+
+    /// Get every receipt that is neither acknowledged nor expired
+    pub async fn get_pending(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+        let sql =
+            "SELECT * FROM receipt WHERE acknowledged = 0 AND expired = 0 ORDER BY receipt_id";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_all(db).await?;
+        Ok(result)
+    }
+
+    pub async fn insert(db: &SqlitePool, request_id: i64, receipt: &str) -> Result<Self, AppError> {
+        let sql = "INSERT INTO receipt(request_id, receipt, created, acknowledged, expired) VALUES ($1, $2, $3, 0, 0) RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(request_id)
+            .bind(receipt)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    /// Stop checking a receipt that pushover no longer knows about, or that can't still be repeating
+    pub async fn set_expired(&self, db: &SqlitePool) -> Result<Self, AppError> {
+        let sql =
+            "UPDATE receipt SET last_checked = $1, expired = 1 WHERE receipt_id = $2 RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .bind(self.receipt_id)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    /// Store the latest state of the receipt, as returned by the Pushover receipts api
+    pub async fn update(&self, db: &SqlitePool, receipt: &Receipt) -> Result<Self, AppError> {
+        let sql = "UPDATE receipt SET last_checked = $1, acknowledged = $2, acknowledged_at = $3, acknowledged_by = $4, acknowledged_by_device = $5, expired = $6 WHERE receipt_id = $7 RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .bind(receipt.acknowledged)
+            .bind(
+                receipt
+                    .acknowledged
+                    .then(|| i64::try_from(receipt.acknowledged_at))
+                    .transpose()?,
+            )
+            .bind(receipt.acknowledged.then_some(&receipt.acknowledged_by))
+            .bind(
+                receipt
+                    .acknowledged
+                    .then_some(&receipt.acknowledged_by_device),
+            )
+            .bind(receipt.expired)
+            .bind(self.receipt_id)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use crate::{
+        S,
+        tests::{setup_test, test_cleanup},
+    };
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_receipt_insert_update() {
+        let (_app_env, db, uuid) = setup_test().await;
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+
+        let result = ModelReceipt::insert(&db, request.request_id, "rcpt").await;
+
+        assert!(result.is_ok());
+        let result = result.unwrap();
+        assert_eq!(result.receipt, "rcpt");
+        assert!(!result.acknowledged);
+        assert!(result.last_checked.is_none());
+        assert_eq!(ModelReceipt::get_pending(&db).await.unwrap().len(), 1);
+
+        // Duplicate receipts are rejected
+        assert!(
+            ModelReceipt::insert(&db, request.request_id, "rcpt")
+                .await
+                .is_err()
+        );
+
+        let mut receipt = Receipt {
+            acknowledged: false,
+            acknowledged_at: 0,
+            acknowledged_by: S!(),
+            acknowledged_by_device: S!(),
+            expired: false,
+            expires_at: 1_360_019_290,
+        };
+        let result = result.update(&db, &receipt).await.unwrap();
+        assert!(result.last_checked.is_some());
+        assert!(result.acknowledged_by.is_none());
+        assert_eq!(ModelReceipt::get_pending(&db).await.unwrap().len(), 1);
+
+        receipt.acknowledged = true;
+        receipt.acknowledged_at = 1_360_019_238;
+        receipt.acknowledged_by = S!("user_key");
+        receipt.acknowledged_by_device = S!("iphone");
+        let result = result.update(&db, &receipt).await.unwrap();
+        assert!(result.acknowledged);
+        assert_eq!(result.acknowledged_at, Some(1_360_019_238));
+        assert_eq!(result.acknowledged_by, Some(S!("user_key")));
+        assert_eq!(result.acknowledged_by_device, Some(S!("iphone")));
+        assert!(ModelReceipt::get_pending(&db).await.unwrap().is_empty());
+        assert_eq!(ModelReceipt::get_all(&db).await.unwrap().len(), 1);
+
+        let result = ModelReceipt::insert(&db, request.request_id, "other")
+            .await
+            .unwrap()
+            .set_expired(&db)
+            .await
+            .unwrap();
+        assert!(result.expired);
+        assert!(!result.acknowledged);
+        assert!(ModelReceipt::get_pending(&db).await.unwrap().is_empty());
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
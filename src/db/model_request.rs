@@ -1,4 +1,4 @@
-use jiff::{SpanRound, ToSpan, Unit, Zoned};
+use jiff::Zoned;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
 use std::time::Duration;
@@ -12,14 +12,30 @@ pub struct ModelRequest {
     pub request_id: i64,
     #[sqlx(try_from = "i64")]
     pub timestamp: u64,
+    /// Set once a notifier, or fallback, has accepted the message
+    pub delivered: bool,
+    /// The request id pushover gave the message
+    pub pushover_request: Option<String>,
+    /// Name of the event, None for requests made before it was stored, which were all online events
+    pub event: Option<String>,
+    /// Dropped by the rate limit, or while boot looping, so never sent
+    pub suppressed: bool,
+    /// Dropped while boot looping, never reported in a rate limit summary
+    pub muted: bool,
 }
 
 impl fmt::Display for ModelRequest {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "request_id: {}, timestamp:{}",
-            self.request_id, self.timestamp,
+            "request_id: {}, timestamp:{}, event: {}, delivered: {}, suppressed: {}, muted: {}, pushover_request: {}",
+            self.request_id,
+            self.timestamp,
+            self.event.as_deref().unwrap_or_default(),
+            self.delivered,
+            self.suppressed,
+            self.muted,
+            self.pushover_request.as_deref().unwrap_or_default()
         )
     }
 }
@@ -48,27 +64,84 @@ impl ModelRequest {
         Ok(result)
     }
 
-    /// Get all request made in the last hour
-    pub async fn get_past_hour(db: &SqlitePool) -> Result<Vec<Self>, AppError> {
+    /// Get all request made in the last `window` seconds
+    pub async fn get_window(db: &SqlitePool, window: u64) -> Result<Vec<Self>, AppError> {
         let sql = "SELECT * FROM request WHERE timestamp BETWEEN $1 AND $2 ORDER BY timestamp";
         let now = i64::try_from(Self::now())?;
-        let one_hour = 1
-            .hour()
-            .round(SpanRound::new().largest(Unit::Second))
-            .map_or(0, |i| i.get_seconds());
         let result = sqlx::query_as::<_, Self>(sql)
-            .bind(now - one_hour)
+            .bind(now.saturating_sub(i64::try_from(window)?))
             .bind(now)
             .fetch_all(db)
             .await?;
         Ok(result)
     }
 
+    /// Get the most recent request made at boot, suppressed or not
+    pub async fn get_last_boot(db: &SqlitePool) -> Result<Option<Self>, AppError> {
+        let sql = "SELECT * FROM request WHERE COALESCE(event, 'online') IN ('online', 'boot loop') ORDER BY request_id DESC LIMIT 1";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_optional(db).await?;
+        Ok(result)
+    }
+
+    /// Get the most recent request of any kind, aka the last time the machine was known to be up
+    pub async fn get_last(db: &SqlitePool) -> Result<Option<Self>, AppError> {
+        let sql = "SELECT * FROM request ORDER BY request_id DESC LIMIT 1";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_optional(db).await?;
+        Ok(result)
+    }
+
+    /// Get every request suppressed by the rate limit since the last `summary_event` request, aka those not yet reported
+    pub async fn get_suppressed(
+        db: &SqlitePool,
+        summary_event: &str,
+    ) -> Result<Vec<Self>, AppError> {
+        let sql = "SELECT * FROM request WHERE suppressed = 1 AND muted = 0 AND request_id > (SELECT COALESCE(MAX(request_id), 0) FROM request WHERE event = $1) ORDER BY request_id";
+        let result = sqlx::query_as::<_, Self>(sql)
+            .bind(summary_event)
+            .fetch_all(db)
+            .await?;
+        Ok(result)
+    }
+
+    pub async fn set_delivered(db: &SqlitePool, request_id: i64) -> Result<(), AppError> {
+        let sql = "UPDATE request SET delivered = 1 WHERE request_id = $1";
+        sqlx::query(sql).bind(request_id).execute(db).await?;
+        Ok(())
+    }
+
+    pub async fn set_pushover_request(
+        db: &SqlitePool,
+        request_id: i64,
+        pushover_request: &str,
+    ) -> Result<(), AppError> {
+        let sql = "UPDATE request SET pushover_request = $1 WHERE request_id = $2";
+        sqlx::query(sql)
+            .bind(pushover_request)
+            .bind(request_id)
+            .execute(db)
+            .await?;
+        Ok(())
+    }
+
     // insert a new request with timestamp
-    pub async fn insert(db: &SqlitePool) -> Result<Self, AppError> {
-        let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING request_id, timestamp";
+    pub async fn insert(db: &SqlitePool, event: &str, suppressed: bool) -> Result<Self, AppError> {
+        let sql =
+            "INSERT INTO request(timestamp, event, suppressed) VALUES ($1, $2, $3) RETURNING *";
         let query = sqlx::query_as::<_, Self>(sql)
             .bind(i64::try_from(Self::now())?)
+            .bind(event)
+            .bind(suppressed)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+
+    /// Insert a request dropped while boot looping, it still counts as a boot, but is never reported
+    pub async fn insert_muted(db: &SqlitePool, event: &str) -> Result<Self, AppError> {
+        let sql = "INSERT INTO request(timestamp, event, suppressed, muted) VALUES ($1, $2, 1, 1) RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(Self::now())?)
+            .bind(event)
             .fetch_one(db)
             .await?;
         Ok(query)
@@ -79,7 +152,10 @@ impl ModelRequest {
 #[expect(clippy::unwrap_used)]
 mod tests {
 
-    use crate::tests::{setup_test, test_cleanup};
+    use crate::{
+        S,
+        tests::{setup_test, test_cleanup},
+    };
 
     use super::*;
 
@@ -88,7 +164,7 @@ mod tests {
         let (_app_env, db, uuid) = setup_test().await;
 
         let now = ModelRequest::now();
-        let result = ModelRequest::insert(&db).await;
+        let result = ModelRequest::insert(&db, "online", false).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -102,7 +178,7 @@ mod tests {
         let (_app_env, db, uuid) = setup_test().await;
 
         let now = ModelRequest::now();
-        let result = ModelRequest::insert(&db).await;
+        let result = ModelRequest::insert(&db, "online", false).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -116,7 +192,7 @@ mod tests {
         let (_app_env, db, uuid) = setup_test().await;
         let now = ModelRequest::now();
         for i in 0..4 {
-            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING request_id, timestamp";
+            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING *";
             sqlx::query_as::<_, ModelRequest>(sql)
                 .bind(i64::try_from(now + i).unwrap())
                 .fetch_one(&db)
@@ -146,12 +222,12 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn model_request_get_last_hour_ok() {
+    async fn model_request_get_window_ok() {
         let (_app_env, db, uuid) = setup_test().await;
 
         let now = i64::try_from(ModelRequest::now()).unwrap();
         for i in 1..=4 {
-            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING request_id, timestamp";
+            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING *";
             let timestamp = now - (60 * (i * 25));
 
             sqlx::query_as::<_, ModelRequest>(sql)
@@ -161,7 +237,7 @@ mod tests {
                 .unwrap();
         }
 
-        let result = ModelRequest::get_past_hour(&db).await;
+        let result = ModelRequest::get_window(&db, 60 * 60).await;
 
         assert!(result.is_ok());
         let result = result.unwrap();
@@ -171,14 +247,100 @@ mod tests {
             ModelRequest {
                 request_id: 2,
                 timestamp: u64::try_from(now - (60 * 50)).unwrap(),
+                delivered: false,
+                pushover_request: None,
+                event: None,
+                suppressed: false,
+                muted: false,
             },
             ModelRequest {
                 request_id: 1,
                 timestamp: u64::try_from(now - (60 * 25)).unwrap(),
+                delivered: false,
+                pushover_request: None,
+                event: None,
+                suppressed: false,
+                muted: false,
             },
         ];
 
         assert_eq!(result, expected);
         test_cleanup(uuid, Some(db)).await;
     }
+
+    #[tokio::test]
+    async fn model_request_set_delivered_pushover_request() {
+        let (_app_env, db, uuid) = setup_test().await;
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+        assert!(!request.delivered);
+        assert!(request.pushover_request.is_none());
+
+        ModelRequest::set_pushover_request(&db, request.request_id, "abc")
+            .await
+            .unwrap();
+        ModelRequest::set_delivered(&db, request.request_id)
+            .await
+            .unwrap();
+
+        let result = ModelRequest::get_all(&db).await.unwrap();
+        assert!(result[0].delivered);
+        assert_eq!(result[0].pushover_request, Some(S!("abc")));
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    async fn model_request_get_suppressed() {
+        let (_app_env, db, uuid) = setup_test().await;
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+        ModelRequest::insert(&db, "suppressed", false)
+            .await
+            .unwrap();
+        ModelRequest::insert(&db, "online", false).await.unwrap();
+        assert!(
+            ModelRequest::get_suppressed(&db, "suppressed")
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+        ModelRequest::insert_muted(&db, "online").await.unwrap();
+        ModelRequest::insert(&db, "service installed", true)
+            .await
+            .unwrap();
+
+        let result = ModelRequest::get_suppressed(&db, "suppressed")
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].request_id, 4);
+        assert_eq!(result[0].event.as_deref(), Some("online"));
+        assert_eq!(result[1].request_id, 6);
+        assert!(
+            result
+                .iter()
+                .all(|i| i.suppressed && !i.muted && !i.delivered)
+        );
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    async fn model_request_get_last_boot() {
+        let (_app_env, db, uuid) = setup_test().await;
+        assert!(ModelRequest::get_last_boot(&db).await.unwrap().is_none());
+        assert!(ModelRequest::get_last(&db).await.unwrap().is_none());
+
+        ModelRequest::insert(&db, "online", false).await.unwrap();
+        ModelRequest::insert(&db, "boot loop", false).await.unwrap();
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+        ModelRequest::insert(&db, "service installed", false)
+            .await
+            .unwrap();
+
+        let result = ModelRequest::get_last_boot(&db).await.unwrap().unwrap();
+        assert_eq!(result.request_id, 3);
+        let result = ModelRequest::get_last(&db).await.unwrap().unwrap();
+        assert_eq!(result.request_id, 4);
+        test_cleanup(uuid, Some(db)).await;
+    }
 }
@@ -0,0 +1,72 @@
+use serde::Deserialize;
+use sqlx::SqlitePool;
+use std::fmt;
+
+use crate::app_error::AppError;
+
+use super::ModelRequest;
+
+/// Written by the shutdown hook, so the next boot knows the previous shutdown was clean
+#[derive(sqlx::FromRow, Debug, Clone, Deserialize)]
+pub struct ModelShutdown {
+    pub shutdown_id: i64,
+    #[sqlx(try_from = "i64")]
+    pub timestamp: u64,
+    /// e.g. reboot or poweroff, None if unknown
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for ModelShutdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "shutdown_id: {}, timestamp: {}, reason: {}",
+            self.shutdown_id,
+            self.timestamp,
+            self.reason.as_deref().unwrap_or_default()
+        )
+    }
+}
+
+impl ModelShutdown {
+    /// The most recent clean shutdown
+    pub async fn get_last(db: &SqlitePool) -> Result<Option<Self>, AppError> {
+        let sql = "SELECT * FROM shutdown ORDER BY shutdown_id DESC LIMIT 1";
+        let result = sqlx::query_as::<_, Self>(sql).fetch_optional(db).await?;
+        Ok(result)
+    }
+
+    pub async fn insert(db: &SqlitePool, reason: Option<&str>) -> Result<Self, AppError> {
+        let sql = "INSERT INTO shutdown (timestamp, reason) VALUES($1, $2) RETURNING *";
+        let query = sqlx::query_as::<_, Self>(sql)
+            .bind(i64::try_from(ModelRequest::now())?)
+            .bind(reason)
+            .fetch_one(db)
+            .await?;
+        Ok(query)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use crate::tests::{setup_test, test_cleanup};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn model_shutdown_insert_get_last() {
+        let (_app_env, db, uuid) = setup_test().await;
+        assert!(ModelShutdown::get_last(&db).await.unwrap().is_none());
+
+        let now = ModelRequest::now();
+        ModelShutdown::insert(&db, None).await.unwrap();
+        ModelShutdown::insert(&db, Some("reboot")).await.unwrap();
+
+        let result = ModelShutdown::get_last(&db).await.unwrap().unwrap();
+        assert_eq!(result.shutdown_id, 2);
+        assert_eq!(result.reason.as_deref(), Some("reboot"));
+        assert!(result.timestamp >= now);
+        test_cleanup(uuid, Some(db)).await;
+    }
+}
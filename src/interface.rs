@@ -0,0 +1,425 @@
+use std::{collections::BTreeMap, fmt, net::IpAddr, str::FromStr};
+
+use ipnet::IpNet;
+
+use crate::{C, S};
+
+#[cfg(target_os = "linux")]
+use crate::service_install::LinuxService;
+
+/// Name of the loopback interface, never reported
+const LOOPBACK: &str = "lo";
+
+/// Matches an interface by name, as a glob, e.g. `veth*`, or an address, as a cidr, e.g. `172.17.0.0/16`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InterfaceRule {
+    Name(String),
+    Cidr(IpNet),
+}
+
+impl FromStr for InterfaceRule {
+    type Err = ();
+
+    /// Interface names can't contain a `/`, so anything with one has to be a cidr
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('/') {
+            s.parse().map(Self::Cidr).map_err(|_| ())
+        } else if s.is_empty() {
+            Err(())
+        } else {
+            Ok(Self::Name(S!(s)))
+        }
+    }
+}
+
+impl InterfaceRule {
+    /// Glob match, `*` matches any run of characters, `?` any single character
+    fn glob(pattern: &str, name: &str) -> bool {
+        let (pattern, name) = (
+            pattern.chars().collect::<Vec<_>>(),
+            name.chars().collect::<Vec<_>>(),
+        );
+        let (mut p, mut n) = (0, 0);
+        // Position of the last `*`, and where in the name it started matching
+        let mut star = None;
+        while n < name.len() {
+            if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+                p += 1;
+                n += 1;
+            } else if p < pattern.len() && pattern[p] == '*' {
+                star = Some((p, n));
+                p += 1;
+            } else if let Some((star_p, star_n)) = star {
+                p = star_p + 1;
+                n = star_n + 1;
+                star = Some((star_p, star_n + 1));
+            } else {
+                return false;
+            }
+        }
+        pattern[p..].iter().all(|i| *i == '*')
+    }
+
+    /// A name rule matches every address of the interface, a cidr rule only the addresses it contains
+    fn matches(&self, name: &str, ip: Option<IpAddr>) -> bool {
+        match self {
+            Self::Name(pattern) => Self::glob(pattern, name),
+            Self::Cidr(net) => ip.is_some_and(|ip| net.contains(&ip)),
+        }
+    }
+}
+
+/// Which interfaces, and addresses, are reported, with no include rules everything not excluded is reported
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InterfaceFilter {
+    pub include: Vec<InterfaceRule>,
+    pub exclude: Vec<InterfaceRule>,
+}
+
+impl InterfaceFilter {
+    /// Exclude rules take priority over include rules
+    fn allows(&self, name: &str, ip: Option<IpAddr>) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|i| i.matches(name, ip)))
+            && !self.exclude.iter().any(|i| i.matches(name, ip))
+    }
+
+    /// Remove each address that isn't allowed, and then each interface left without an address
+    /// An interface that never had an address is kept if its name is allowed
+    fn apply(&self, interfaces: Vec<Interface>) -> Vec<Interface> {
+        interfaces
+            .into_iter()
+            .filter_map(|mut interface| {
+                if interface.addresses.is_empty() {
+                    return self.allows(&interface.name, None).then_some(interface);
+                }
+                let name = C!(interface.name);
+                interface
+                    .addresses
+                    .retain(|ip| self.allows(&name, Some(*ip)));
+                (!interface.addresses.is_empty()).then_some(interface)
+            })
+            .collect()
+    }
+}
+
+/// A network interface, with its mac address, link state, and addresses, where known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interface {
+    pub name: String,
+    pub mac: Option<String>,
+    pub state: Option<String>,
+    pub addresses: Vec<IpAddr>,
+}
+
+impl fmt::Display for Interface {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {}",
+            self.name,
+            self.mac.as_deref().unwrap_or("unknown"),
+            self.state.as_deref().unwrap_or("unknown"),
+            Self::fmt_addresses(&self.addresses)
+        )
+    }
+}
+
+impl Interface {
+    /// Space separated addresses, or "no address"
+    pub fn fmt_addresses(addresses: &[IpAddr]) -> String {
+        if addresses.is_empty() {
+            return S!("no address");
+        }
+        addresses
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Every non-loopback interface allowed by the filter, sorted by name, each with its sorted addresses
+    /// On linux interfaces without an address are included, so a nic that failed to come up is still seen
+    pub fn get_all(filter: &InterfaceFilter) -> Vec<Self> {
+        #[cfg(target_os = "linux")]
+        let links = LinuxService::get_links();
+        #[cfg(not(target_os = "linux"))]
+        let links = vec![];
+
+        let mut interfaces = links
+            .into_iter()
+            .map(|i| (C!(i.name), i))
+            .collect::<BTreeMap<_, _>>();
+        for (name, ip) in local_ip_address::list_afinet_netifas().unwrap_or_default() {
+            interfaces
+                .entry(C!(name))
+                .or_insert_with(|| Self {
+                    name,
+                    mac: None,
+                    state: None,
+                    addresses: vec![],
+                })
+                .addresses
+                .push(ip);
+        }
+        filter.apply(
+            interfaces
+                .into_values()
+                .filter(|i| i.name != LOOPBACK && !i.addresses.iter().any(IpAddr::is_loopback))
+                .map(|mut i| {
+                    i.addresses.sort_unstable();
+                    i.addresses.dedup();
+                    i
+                })
+                .collect(),
+        )
+    }
+
+    /// Each interface with its addresses, e.g. "eth0 192.168.1.2, wlan0 no address"
+    pub fn fmt_all(interfaces: &[Self]) -> String {
+        interfaces
+            .iter()
+            .map(|i| format!("{} {}", i.name, Self::fmt_addresses(&i.addresses)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// The address reported as the local ip, the os choice if it's still shown, else the first shown ipv4, then any shown address
+    pub fn local_ip(interfaces: &[Self]) -> Option<IpAddr> {
+        let addresses = interfaces
+            .iter()
+            .flat_map(|i| i.addresses.iter().copied())
+            .collect::<Vec<_>>();
+        local_ip_address::local_ip()
+            .ok()
+            .filter(|i| addresses.contains(i))
+            .or_else(|| addresses.iter().find(|i| i.is_ipv4()).copied())
+            .or_else(|| addresses.first().copied())
+    }
+
+    /// Each interface that has appeared, disappeared, or changed its addresses, link state, or mac address, between two snapshots
+    pub fn changes(old: &[Self], new: &[Self]) -> Vec<String> {
+        let unknown = |i: Option<&str>| S!(i.unwrap_or("unknown"));
+        let mut changes = vec![];
+        for interface in new {
+            let Some(previous) = old.iter().find(|i| i.name == interface.name) else {
+                changes.push(format!(
+                    "{} added {}",
+                    interface.name,
+                    Self::fmt_addresses(&interface.addresses)
+                ));
+                continue;
+            };
+            if previous.addresses != interface.addresses {
+                changes.push(format!(
+                    "{} {} -> {}",
+                    interface.name,
+                    Self::fmt_addresses(&previous.addresses),
+                    Self::fmt_addresses(&interface.addresses)
+                ));
+            }
+            if previous.state != interface.state {
+                changes.push(format!(
+                    "{} {} -> {}",
+                    interface.name,
+                    unknown(previous.state.as_deref()),
+                    unknown(interface.state.as_deref())
+                ));
+            }
+            if previous.mac != interface.mac {
+                changes.push(format!(
+                    "{} mac {} -> {}",
+                    interface.name,
+                    unknown(previous.mac.as_deref()),
+                    unknown(interface.mac.as_deref())
+                ));
+            }
+        }
+        for interface in old {
+            if !new.iter().any(|i| i.name == interface.name) {
+                changes.push(format!("{} removed", interface.name));
+            }
+        }
+        changes
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::net::Ipv4Addr;
+
+    use super::*;
+
+    fn gen_interface(name: &str, state: &str, addresses: &[[u8; 4]]) -> Interface {
+        Interface {
+            name: name.to_owned(),
+            mac: Some(String::from("02:00:00:00:00:01")),
+            state: Some(state.to_owned()),
+            addresses: addresses
+                .iter()
+                .map(|i| IpAddr::V4(Ipv4Addr::from(*i)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    /// No changes between identical snapshots
+    fn test_interface_changes_none() {
+        let interfaces = vec![
+            gen_interface("eth0", "up", &[[192, 168, 1, 2]]),
+            gen_interface("eth1", "down", &[]),
+        ];
+        assert!(Interface::changes(&interfaces, &interfaces).is_empty());
+    }
+
+    #[test]
+    /// Added, removed, readdressed, link state and mac changes all reported
+    fn test_interface_changes() {
+        let old = vec![
+            gen_interface("eth0", "up", &[[192, 168, 1, 2]]),
+            gen_interface("eth1", "up", &[[10, 0, 0, 2]]),
+            gen_interface("wlan0", "up", &[]),
+            gen_interface("wg0", "up", &[[10, 8, 0, 1]]),
+        ];
+        let mut wlan0 = gen_interface("wlan0", "up", &[]);
+        wlan0.mac = Some(String::from("02:00:00:00:00:02"));
+        let new = vec![
+            gen_interface("eth0", "up", &[[192, 168, 1, 3]]),
+            gen_interface("eth1", "down", &[]),
+            wlan0,
+            gen_interface("eth2", "up", &[[10, 0, 1, 2], [10, 0, 1, 3]]),
+        ];
+        let result = Interface::changes(&old, &new);
+        assert_eq!(
+            result,
+            vec![
+                "eth0 192.168.1.2 -> 192.168.1.3",
+                "eth1 10.0.0.2 -> no address",
+                "eth1 up -> down",
+                "wlan0 mac 02:00:00:00:00:01 -> 02:00:00:00:00:02",
+                "eth2 added 10.0.1.2 10.0.1.3",
+                "wg0 removed",
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    /// Loopback never included, and addresses sorted
+    fn test_interface_get_all() {
+        let result = Interface::get_all(&InterfaceFilter::default());
+        assert!(result.iter().all(|i| i.name != LOOPBACK));
+        assert!(result.iter().all(|i| i.addresses.is_sorted()));
+        let name = C!(result.first().unwrap().name);
+
+        let filter = InterfaceFilter {
+            include: vec![],
+            exclude: vec![InterfaceRule::Name(C!(name))],
+        };
+        let result = Interface::get_all(&filter);
+        assert!(result.iter().all(|i| i.name != name));
+    }
+
+    #[test]
+    /// Names without a `/` are globs, anything else has to be a valid cidr
+    fn test_interface_rule_parse() {
+        assert_eq!(
+            "veth*".parse::<InterfaceRule>(),
+            Ok(InterfaceRule::Name(S!("veth*")))
+        );
+        assert_eq!(
+            "172.17.0.0/16".parse::<InterfaceRule>(),
+            Ok(InterfaceRule::Cidr("172.17.0.0/16".parse().unwrap()))
+        );
+        assert_eq!(
+            "fd7a:115c:a1e0::/48".parse::<InterfaceRule>(),
+            Ok(InterfaceRule::Cidr("fd7a:115c:a1e0::/48".parse().unwrap()))
+        );
+        assert!("172.17.0.0/33".parse::<InterfaceRule>().is_err());
+        assert!("docker/0".parse::<InterfaceRule>().is_err());
+        assert!("".parse::<InterfaceRule>().is_err());
+    }
+
+    #[test]
+    fn test_interface_rule_glob() {
+        assert!(InterfaceRule::glob("docker0", "docker0"));
+        assert!(!InterfaceRule::glob("docker0", "docker01"));
+        assert!(InterfaceRule::glob("veth*", "veth1a2b3c"));
+        assert!(InterfaceRule::glob("veth*", "veth"));
+        assert!(!InterfaceRule::glob("veth*", "eth0"));
+        assert!(InterfaceRule::glob("*0", "tailscale0"));
+        assert!(InterfaceRule::glob("br-*-*", "br-1a2b-3c"));
+        assert!(!InterfaceRule::glob("br-*-*", "br-1a2b"));
+        assert!(InterfaceRule::glob("eth?", "eth1"));
+        assert!(!InterfaceRule::glob("eth?", "eth10"));
+        assert!(InterfaceRule::glob("*", ""));
+    }
+
+    #[test]
+    /// Excludes beat includes, cidr rules filter single addresses, and interfaces left without an address are hidden
+    fn test_interface_filter_apply() {
+        let interfaces = vec![
+            gen_interface("docker0", "up", &[[172, 17, 0, 1]]),
+            gen_interface("eth0", "up", &[[192, 168, 1, 2], [172, 17, 5, 5]]),
+            gen_interface("eth1", "down", &[]),
+            gen_interface("tailscale0", "up", &[[100, 64, 0, 2]]),
+            gen_interface("veth1a2b", "up", &[[10, 0, 0, 2]]),
+        ];
+        let names = |interfaces: &[Interface]| {
+            interfaces
+                .iter()
+                .map(|i| Interface::fmt_all(std::slice::from_ref(i)))
+                .collect::<Vec<_>>()
+        };
+
+        let result = InterfaceFilter::default().apply(C!(interfaces));
+        assert_eq!(result, interfaces);
+
+        let filter = InterfaceFilter {
+            include: vec![],
+            exclude: vec![
+                "docker0".parse().unwrap(),
+                "veth*".parse().unwrap(),
+                "172.16.0.0/12".parse().unwrap(),
+            ],
+        };
+        assert_eq!(
+            names(&filter.apply(C!(interfaces))),
+            vec![
+                "eth0 192.168.1.2",
+                "eth1 no address",
+                "tailscale0 100.64.0.2"
+            ]
+        );
+
+        let filter = InterfaceFilter {
+            include: vec![
+                "tailscale0".parse().unwrap(),
+                "192.168.0.0/16".parse().unwrap(),
+            ],
+            exclude: vec![],
+        };
+        assert_eq!(
+            names(&filter.apply(C!(interfaces))),
+            vec!["eth0 192.168.1.2", "tailscale0 100.64.0.2"]
+        );
+
+        let filter = InterfaceFilter {
+            include: vec!["eth*".parse().unwrap()],
+            exclude: vec!["eth0".parse().unwrap()],
+        };
+        assert_eq!(names(&filter.apply(interfaces)), vec!["eth1 no address"]);
+    }
+
+    #[test]
+    fn test_interface_local_ip() {
+        assert!(Interface::local_ip(&[]).is_none());
+        let interfaces = vec![
+            gen_interface("eth0", "up", &[]),
+            gen_interface("eth1", "up", &[[10, 0, 0, 2], [192, 168, 1, 2]]),
+        ];
+        let result = Interface::local_ip(&interfaces).unwrap();
+        assert!(result.is_ipv4());
+        assert!(interfaces[1].addresses.contains(&result));
+    }
+}
@@ -0,0 +1,247 @@
+use std::{net::IpAddr, str::FromStr};
+
+use reqwest::Client;
+use serde_json::Value;
+
+use crate::S;
+
+/// What's my ipv4 url
+const URL_V4: &str = "https://myipv4.p1.opendns.com/get_my_ip";
+/// What's my ipv6 url
+const URL_V6: &str = "https://myipv6.p1.opendns.com/get_my_ip";
+
+/// Disables the lookup, instead of a list of providers
+pub const PROVIDERS_NONE: &str = "none";
+
+/// How a provider returns the ip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IpFormat {
+    /// The ip alone, surrounding whitespace is ignored
+    Text,
+    /// A json string, found by following the dot separated path, array items by index, e.g. `ip` or `data.0.address`
+    Json(Vec<String>),
+}
+
+/// A what's my ip api
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpProvider {
+    pub url: String,
+    pub format: IpFormat,
+}
+
+impl FromStr for IpProvider {
+    type Err = ();
+
+    /// `<url> [text|json:<path>]`, the format defaults to text
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let url = parts.next().ok_or(())?;
+        url::Url::parse(url).map_err(|_| ())?;
+        let format = match parts.next() {
+            None | Some("text") => IpFormat::Text,
+            Some(format) => {
+                let path = format.strip_prefix("json:").ok_or(())?;
+                if path.is_empty() || path.split('.').any(str::is_empty) {
+                    return Err(());
+                }
+                IpFormat::Json(path.split('.').map(String::from).collect())
+            }
+        };
+        if parts.next().is_some() {
+            return Err(());
+        }
+        Ok(Self {
+            url: S!(url),
+            format,
+        })
+    }
+}
+
+impl IpProvider {
+    /// The ip in a response body, None if it can't be found, or isn't a valid ip
+    fn parse(&self, body: &str) -> Option<IpAddr> {
+        match &self.format {
+            IpFormat::Text => body.trim().parse().ok(),
+            IpFormat::Json(path) => {
+                let json = serde_json::from_str::<Value>(body).ok()?;
+                path.iter()
+                    .try_fold(&json, |value, key| match value {
+                        Value::Array(items) => key.parse::<usize>().ok().and_then(|i| items.get(i)),
+                        _ => value.get(key),
+                    })?
+                    .as_str()?
+                    .trim()
+                    .parse()
+                    .ok()
+            }
+        }
+    }
+
+    /// Request the ip from the provider, None on any error
+    pub async fn get(&self, client: &Client) -> Option<IpAddr> {
+        let response = client
+            .get(&self.url)
+            .send()
+            .await
+            .ok()?
+            .error_for_status()
+            .ok()?;
+        self.parse(&response.text().await.ok()?)
+    }
+}
+
+/// The providers for each ip version, tried in order, an empty list disables that lookup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IpProviders {
+    pub v4: Vec<IpProvider>,
+    pub v6: Vec<IpProvider>,
+}
+
+impl Default for IpProviders {
+    fn default() -> Self {
+        let opendns = |url: &str| IpProvider {
+            url: S!(url),
+            format: IpFormat::Json(vec![S!("ip")]),
+        };
+        Self {
+            v4: vec![opendns(URL_V4)],
+            v6: vec![opendns(URL_V6)],
+        }
+    }
+}
+
+impl IpProviders {
+    /// Providers that return nothing, so that tests never make a real lookup
+    #[cfg(test)]
+    pub const fn none() -> Self {
+        Self {
+            v4: vec![],
+            v6: vec![],
+        }
+    }
+
+    /// A text provider for each version, usually pointing at a local stand-in
+    #[cfg(test)]
+    pub fn text(v4: &str, v6: &str) -> Self {
+        let text = |url: &str| IpProvider {
+            url: S!(url),
+            format: IpFormat::Text,
+        };
+        Self {
+            v4: vec![text(v4)],
+            v6: vec![text(v6)],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use super::*;
+    use crate::tests::StandIn;
+
+    fn gen_provider(url: &str, format: IpFormat) -> IpProvider {
+        IpProvider {
+            url: S!(url),
+            format,
+        }
+    }
+
+    #[test]
+    /// Format defaults to text, json needs a non-empty path, and the url has to be valid
+    fn test_ip_provider_parse_entry() {
+        assert_eq!(
+            "https://api.ipify.org".parse::<IpProvider>(),
+            Ok(gen_provider("https://api.ipify.org", IpFormat::Text))
+        );
+        assert_eq!(
+            "https://ipv4.icanhazip.com  text".parse::<IpProvider>(),
+            Ok(gen_provider("https://ipv4.icanhazip.com", IpFormat::Text))
+        );
+        assert_eq!(
+            "https://myipv4.p1.opendns.com/get_my_ip json:ip".parse::<IpProvider>(),
+            Ok(gen_provider(
+                "https://myipv4.p1.opendns.com/get_my_ip",
+                IpFormat::Json(vec![S!("ip")])
+            ))
+        );
+        assert_eq!(
+            "http://echo.internal:8080/ip json:data.0.address".parse::<IpProvider>(),
+            Ok(gen_provider(
+                "http://echo.internal:8080/ip",
+                IpFormat::Json(vec![S!("data"), S!("0"), S!("address")])
+            ))
+        );
+
+        for invalid in [
+            "",
+            "api.ipify.org",
+            "https://api.ipify.org xml",
+            "https://api.ipify.org json:",
+            "https://api.ipify.org json:data..ip",
+            "https://api.ipify.org text extra",
+        ] {
+            assert!(invalid.parse::<IpProvider>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    /// The ip found in plain text, and json, responses
+    fn test_ip_provider_parse_response() {
+        let text = gen_provider("", IpFormat::Text);
+        assert_eq!(
+            text.parse("203.0.113.7\n"),
+            Some(IpAddr::V4(Ipv4Addr::new(203, 0, 113, 7)))
+        );
+        assert_eq!(text.parse("2001:db8::1"), "2001:db8::1".parse().ok());
+        assert_eq!(text.parse("<html>blocked</html>"), None);
+        assert_eq!(text.parse(""), None);
+
+        let json = gen_provider("", IpFormat::Json(vec![S!("ip")]));
+        assert_eq!(
+            json.parse(r#"{"ip":"::1"}"#),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+        assert_eq!(json.parse(r#"{"address":"::1"}"#), None);
+        assert_eq!(json.parse(r#"{"ip":1}"#), None);
+        assert_eq!(json.parse("::1"), None);
+
+        let nested = gen_provider("", IpFormat::Json(vec![S!("data"), S!("1"), S!("address")]));
+        assert_eq!(
+            nested.parse(r#"{"data":[{"address":"10.0.0.1"},{"address":"10.0.0.2"}]}"#),
+            Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)))
+        );
+        assert_eq!(nested.parse(r#"{"data":[{"address":"10.0.0.1"}]}"#), None);
+    }
+
+    #[tokio::test]
+    /// Each format requested from a stand-in, and any error status treated as no response
+    async fn test_ip_provider_get() {
+        let client = Client::new();
+
+        let stand_in = StandIn::start(200, "192.0.2.1\n").await;
+        let result = gen_provider(&stand_in.url, IpFormat::Text)
+            .get(&client)
+            .await;
+        assert_eq!(result, Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))));
+        assert_eq!(stand_in.requests()[0].method, "GET");
+
+        let stand_in = StandIn::start(200, r#"{"ip":"::1"}"#).await;
+        let result = gen_provider(&stand_in.url, IpFormat::Json(vec![S!("ip")]))
+            .get(&client)
+            .await;
+        assert_eq!(result, Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+
+        let stand_in = StandIn::start(503, "192.0.2.1").await;
+        let result = gen_provider(&stand_in.url, IpFormat::Text)
+            .get(&client)
+            .await;
+        assert!(result.is_none());
+
+        let result = gen_provider("http://127.0.0.1:1", IpFormat::Text)
+            .get(&client)
+            .await;
+        assert!(result.is_none());
+    }
+}
@@ -8,16 +8,24 @@ use db::{ModelSkipRequest, init_db};
 use fd_lock::RwLock;
 use parse_cli::CliArgs;
 use request::PushRequest;
+use tokio::sync::watch;
 use tracing_subscriber::{fmt, layer::SubscriberExt};
 
 mod app_env;
 mod app_error;
+mod daemon;
 mod db;
+mod interface;
+mod ip_provider;
+mod notifier;
 mod parse_cli;
 mod request;
+mod secret;
 mod service_install;
 
 const LOGS_NAME: &str = "log";
+/// The most time a shutdown message can take, so that it never delays shutdown by much, kept below the systemd TimeoutStopSec
+const SHUTDOWN_DEADLINE: std::time::Duration = std::time::Duration::from_secs(10);
 
 /// Simple macro to create a new String, or convert from a &str to  a String - basically just gets rid of String::from() / .to_owned() etc
 #[macro_export]
@@ -57,23 +65,29 @@ pub fn exit(message: &str, code: &Code) {
     }
 }
 
+/// Log to stdout, and as JSON to the given writer
+fn gen_subscriber<W>(level: tracing::Level, writer: W) -> impl tracing::Subscriber + Send + Sync
+where
+    W: for<'a> fmt::MakeWriter<'a> + Send + Sync + 'static,
+{
+    let log_fmt = fmt::Layer::default()
+        .json()
+        .flatten_event(true)
+        .with_writer(writer);
+
+    fmt::Subscriber::builder()
+        .with_file(true)
+        .with_line_number(true)
+        .with_max_level(level)
+        .finish()
+        .with(log_fmt)
+}
+
 // Tracing to a file and stdout
 fn setup_tracing(app_env: &AppEnv) -> Result<(), AppError> {
     let logfile = tracing_appender::rolling::never(&app_env.location_base, LOGS_NAME);
 
-    let log_fmt = fmt::Layer::default()
-        .json()
-        .flatten_event(true)
-        .with_writer(logfile);
-
-    match tracing::subscriber::set_global_default(
-        fmt::Subscriber::builder()
-            .with_file(true)
-            .with_line_number(true)
-            .with_max_level(app_env.log_level)
-            .finish()
-            .with(log_fmt),
-    ) {
+    match tracing::subscriber::set_global_default(gen_subscriber(app_env.log_level, logfile)) {
         Ok(()) => Ok(()),
         Err(e) => {
             println!("{e:?}");
@@ -82,25 +96,55 @@ fn setup_tracing(app_env: &AppEnv) -> Result<(), AppError> {
     }
 }
 
+/// Wait for ctrl+c, or a SIGTERM on unix, returns the name of the signal
+async fn wait_signal() -> &'static str {
+    #[cfg(unix)]
+    if let Ok(mut sigterm) =
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+    {
+        return tokio::select! {
+            _ = tokio::signal::ctrl_c() => "ctrl+c",
+            _ = sigterm.recv() => "sigterm",
+        };
+    }
+    tokio::signal::ctrl_c().await.ok();
+    "ctrl+c"
+}
+
 /// Spawn a thread to watch for exit signals, so can show cursor correctly
-fn tokio_signal(app_env: &AppEnv) {
+/// A running daemon is told to shut down via the returned receiver, otherwise, once it's been dropped, exit straight away
+fn tokio_signal(app_env: &AppEnv) -> watch::Receiver<bool> {
+    let (tx, rx) = watch::channel(false);
     let app_env = C!(app_env);
     tokio::spawn(async move {
-        tokio::signal::ctrl_c().await.ok();
-        app_env.rm_lock_file();
-        exit("ctrl+c", &Code::Invalid);
+        let signal = wait_signal().await;
+        if tx.send(true).is_err() {
+            app_env.rm_lock_file();
+            exit(signal, &Code::Invalid);
+        }
     });
+    rx
 }
 
-fn is_single_instance(app_env: &AppEnv) -> Result<bool, AppError> {
+/// Open the lock file, only a single instance is able to hold the write lock on it
+fn open_lock_file(app_env: &AppEnv) -> Result<RwLock<std::fs::File>, AppError> {
     let lock_file = std::fs::OpenOptions::new()
         .read(true)
         .write(true)
         .truncate(false)
         .create(true)
         .open(&app_env.location_lock)?;
-    let mut lock_file = RwLock::new(lock_file);
-    Ok(lock_file.try_write().is_ok())
+    Ok(RwLock::new(lock_file))
+}
+
+/// Record the shutdown, and send the going offline message, the database setup is included in the deadline
+/// Doesn't take the instance lock, as systemd runs ExecStop while the service can still be running
+async fn run_shutdown(app_env: &AppEnv, reason: Option<&'static str>) -> Result<(), AppError> {
+    tokio::time::timeout(SHUTDOWN_DEADLINE, async {
+        let db = init_db(app_env).await?;
+        PushRequest::make_shutdown_request(app_env, &db, reason).await
+    })
+    .await?
 }
 
 #[tokio::main]
@@ -108,12 +152,34 @@ async fn main() -> Result<(), AppError> {
     let cli: CliArgs = CliArgs::new();
     let app_env = AppEnv::get();
 
-    tokio_signal(&app_env);
+    if cli.shutdown {
+        setup_tracing(&app_env)?;
+        // The outbox & receipts are left until the next boot, so shutdown isn't delayed
+        #[cfg(target_os = "linux")]
+        let reason = service_install::LinuxService::get_shutdown_reason();
+        #[cfg(not(target_os = "linux"))]
+        let reason = None;
+        if let Err(e) = run_shutdown(&app_env, reason).await {
+            tracing::error!("shutdown: {e}");
+        }
+        return Ok(());
+    }
+
+    let shutdown = tokio_signal(&app_env);
+    // Only kept by the daemon, so that a signal exits a one-shot run straight away
+    let shutdown = cli.daemon.then_some(shutdown);
 
-    if is_single_instance(&app_env)? {
+    let mut lock_file = open_lock_file(&app_env)?;
+    // Held for the whole lifetime of a daemon, so only a single instance runs at a time, a one-shot run releases it before polling receipts
+    if let Ok(guard) = lock_file.try_write() {
         setup_tracing(&app_env)?;
+        tracing::debug!("{app_env:?}");
         let db = init_db(&app_env).await?;
 
+        if let Err(e) = PushRequest::retry_outbox(&app_env, &db).await {
+            tracing::error!("outbox: {e}");
+        }
+
         if let Ok(str) = service_install::check(&cli, &app_env, &db).await {
             if let Some(status) = str {
                 PushRequest::from(status)
@@ -121,11 +187,35 @@ async fn main() -> Result<(), AppError> {
                     .await?;
             } else if let Some(skip_request) = ModelSkipRequest::get(&db).await
                 && !skip_request.skip
+                && let Err(e) = PushRequest::Online.make_request(&app_env, &db).await
             {
-                PushRequest::Online.make_request(&app_env, &db).await?;
+                // The daemon still needs to start, the outbox retries the message
+                if shutdown.is_none() {
+                    return Err(e);
+                }
+                tracing::error!("online: {e}");
+            }
+        }
+        if let Err(e) = PushRequest::check_interfaces(
+            &app_env,
+            &db,
+            interface::Interface::get_all(&app_env.interface_filter),
+        )
+        .await
+        {
+            tracing::error!("interfaces: {e}");
+        }
+        if let Some(shutdown) = shutdown {
+            daemon::run(&app_env, &db, shutdown).await;
+            app_env.rm_lock_file();
+        } else {
+            // Receipts can be pending for hours, which would otherwise block every other invocation
+            app_env.rm_lock_file();
+            drop(guard);
+            if let Err(e) = PushRequest::poll_receipts(&app_env, &db).await {
+                tracing::error!("receipts: {e}");
             }
         }
-        app_env.rm_lock_file();
     }
 
     Ok(())
@@ -139,15 +229,40 @@ mod tests {
     use uuid::Uuid;
 
     use super::*;
-    use std::path::PathBuf;
+    use crate::{
+        app_env::{BootLoop, RateLimit},
+        db::{ModelRequest, ModelShutdown},
+        notifier::{
+            Backend, Discord, Email, Gotify, MQTT_TOPIC, Message, Mqtt, Ntfy, NtfyAuth, ParseMode,
+            Pushover, PushoverOptions, SMTP_SUBJECT, SmtpSecurity, Telegram, WEBHOOK_BODY, Webhook,
+        },
+        secret::Secret,
+    };
+    use std::{
+        collections::HashMap,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+        path::PathBuf,
+        sync::{Arc, Mutex},
+    };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
 
     pub fn gen_app_env(name: Uuid) -> AppEnv {
         AppEnv {
             timezone: TimeZone::UTC,
             log_level: tracing::Level::INFO,
-            token_app: S!("test_token_app"),
-            token_user: S!("test_token_user"),
+            notifiers: vec![Backend::Pushover(gen_pushover("http://127.0.0.1:1"))],
+            fallbacks: vec![],
             machine_name: S!("test_machine"),
+            rate_limit: RateLimit::default(),
+            boot_loop: BootLoop::default(),
+            heartbeat: 0,
+            ip_check: 0,
+            interface_check: 0,
+            interface_filter: interface::InterfaceFilter::default(),
+            ip_providers: ip_provider::IpProviders::none(),
 
             #[cfg(target_os = "linux")]
             location_sqlite: PathBuf::from(format!("/dev/shm/{name}.db")),
@@ -165,6 +280,231 @@ mod tests {
         }
     }
 
+    /// A webhook notifier, using the default body
+    pub fn gen_webhook(url: &str) -> Webhook {
+        Webhook {
+            url: Secret::from(url),
+            method: reqwest::Method::POST,
+            headers: vec![],
+            body: S!(WEBHOOK_BODY),
+        }
+    }
+
+    /// A pushover notifier, with the default options
+    pub fn gen_pushover(url: &str) -> Pushover {
+        Pushover {
+            url: S!(url),
+            token_app: Secret::from("test_token_app"),
+            token_user: Secret::from("test_token_user"),
+            online: PushoverOptions::default(),
+            service: PushoverOptions::default(),
+            status: PushoverOptions::default(),
+        }
+    }
+
+    /// Pushover options for an emergency priority message, which returns a receipt
+    pub fn gen_emergency() -> PushoverOptions {
+        PushoverOptions {
+            priority: 2,
+            retry: 30,
+            expire: 60,
+            ..PushoverOptions::default()
+        }
+    }
+
+    /// An ntfy notifier, with the given auth
+    pub fn gen_ntfy(url: &str, auth: Option<NtfyAuth>) -> Ntfy {
+        Ntfy {
+            url: format!("{url}/"),
+            topic: S!("test_topic"),
+            auth,
+            priority: 4,
+            tags: vec![S!("computer"), S!("sysup")],
+        }
+    }
+
+    /// A gotify notifier, sending either plain text or markdown
+    pub fn gen_gotify(url: &str, markdown: bool) -> Gotify {
+        Gotify {
+            url: S!(url),
+            token: Secret::from("test_token"),
+            title: None,
+            priority: 8,
+            markdown,
+        }
+    }
+
+    /// A telegram notifier, using the given parse mode
+    pub fn gen_telegram(url: &str, parse_mode: ParseMode) -> Telegram {
+        Telegram {
+            url: S!(url),
+            token: Secret::from("123:test_token"),
+            chat_id: S!("-100123"),
+            thread_id: None,
+            parse_mode,
+        }
+    }
+
+    /// An email notifier, for a plain smtp server on localhost
+    pub fn gen_email(port: u16, to: &[&str]) -> Email {
+        Email {
+            host: S!("127.0.0.1"),
+            port,
+            security: SmtpSecurity::Plain,
+            credentials: None,
+            from: "sysup <sysup@example.com>".parse().unwrap(),
+            to: to.iter().map(|i| i.parse().unwrap()).collect(),
+            subject: S!(SMTP_SUBJECT),
+        }
+    }
+
+    /// An mqtt notifier, for a broker on localhost, using the default topic
+    pub fn gen_mqtt(port: u16, qos: rumqttc::QoS) -> Mqtt {
+        Mqtt {
+            host: S!("127.0.0.1"),
+            port,
+            tls: false,
+            credentials: None,
+            topic: S!(MQTT_TOPIC),
+            qos,
+        }
+    }
+
+    /// A request as received by the StandIn server, header names are lowercase
+    #[derive(Debug, Clone)]
+    pub struct CapturedRequest {
+        pub method: String,
+        pub path: String,
+        pub headers: HashMap<String, String>,
+        pub body: String,
+    }
+
+    /// Minimal local http server, used in place of the real notification apis
+    /// Responds to every request with the given status & body, and records each request received
+    pub struct StandIn {
+        pub url: String,
+        requests: Arc<Mutex<Vec<CapturedRequest>>>,
+    }
+
+    impl StandIn {
+        pub async fn start(status: u16, body: &str) -> Self {
+            Self::start_with_headers(status, &[], body).await
+        }
+
+        /// Same as `start`, but every response also includes the given headers
+        pub async fn start_with_headers(status: u16, headers: &[(&str, &str)], body: &str) -> Self {
+            Self::start_inner(0, status, headers, body).await
+        }
+
+        /// Same as `start`, but the first `failures` requests are responded to with a 500
+        pub async fn start_after_failures(failures: usize, status: u16, body: &str) -> Self {
+            Self::start_inner(failures, status, &[], body).await
+        }
+
+        async fn start_inner(
+            failures: usize,
+            status: u16,
+            headers: &[(&str, &str)],
+            body: &str,
+        ) -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let requests = Arc::new(Mutex::new(vec![]));
+            let headers = headers
+                .iter()
+                .map(|(k, v)| format!("{k}: {v}\r\n"))
+                .collect::<String>();
+            let response = format!(
+                "HTTP/1.1 {status} StandIn\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{headers}Connection: close\r\n\r\n{body}",
+                body.len()
+            );
+            let failure = "HTTP/1.1 500 StandIn\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+            let captured = Arc::clone(&requests);
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    let request = Self::read_request(&mut stream).await;
+                    let count = {
+                        let mut captured = captured.lock().unwrap();
+                        captured.push(request);
+                        captured.len()
+                    };
+                    let response = if count <= failures {
+                        failure
+                    } else {
+                        response.as_str()
+                    };
+                    stream.write_all(response.as_bytes()).await.ok();
+                    stream.shutdown().await.ok();
+                }
+            });
+            Self { url, requests }
+        }
+
+        /// Read the headers, and then Content-Length bytes of body
+        async fn read_request(stream: &mut tokio::net::TcpStream) -> CapturedRequest {
+            let mut data = vec![];
+            let mut buf = [0u8; 4096];
+            let header_end = loop {
+                let n = stream.read(&mut buf).await.unwrap();
+                data.extend_from_slice(&buf[..n]);
+                if let Some(i) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break i;
+                }
+                assert!(n > 0, "connection closed before headers received");
+            };
+            let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+            let mut lines = head.lines();
+            let mut start = lines.next().unwrap_or_default().split_whitespace();
+            let method = S!(start.next().unwrap_or_default());
+            let path = S!(start.next().unwrap_or_default());
+            let headers = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(k, v)| (k.trim().to_lowercase(), S!(v.trim())))
+                .collect::<HashMap<_, _>>();
+            let length = headers
+                .get("content-length")
+                .and_then(|i| i.parse::<usize>().ok())
+                .unwrap_or_default();
+            while data.len() < header_end + 4 + length {
+                let n = stream.read(&mut buf).await.unwrap();
+                if n == 0 {
+                    break;
+                }
+                data.extend_from_slice(&buf[..n]);
+            }
+            CapturedRequest {
+                method,
+                path,
+                headers,
+                body: String::from_utf8_lossy(&data[header_end + 4..]).to_string(),
+            }
+        }
+
+        /// All the requests received so far
+        pub fn requests(&self) -> Vec<CapturedRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// A message, as would be generated by PushRequest::Online
+    pub fn gen_message() -> Message {
+        Message {
+            machine_name: S!("test_machine"),
+            event: S!("online"),
+            timestamp: S!("2025-01-01 12:00:00 Europe/London"),
+            unix_timestamp: 1_735_732_800,
+            timezone: S!("Europe/London"),
+            local_ip: S!("192.168.0.10"),
+            ipv4: Some(IpAddr::V4(Ipv4Addr::LOCALHOST)),
+            ipv6: Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+            interfaces: S!("eth0 192.168.0.10"),
+            text: S!(
+                "test_machine online @ 2025-01-01 12:00:00 Europe/London 192.168.0.10 127.0.0.1 ::1"
+            ),
+            escalated: false,
+        }
+    }
+
     pub async fn setup_test() -> (AppEnv, SqlitePool, Uuid) {
         let uuid = Uuid::new_v4();
         let mut app_env = gen_app_env(uuid);
@@ -191,4 +531,110 @@ mod tests {
         tokio::fs::remove_file(sql_sham).await.ok();
         tokio::fs::remove_file(sql_name).await.ok();
     }
+
+    /// Collects everything written by the JSON log layer
+    #[derive(Clone, Default)]
+    struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for LogBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl fmt::MakeWriter<'_> for LogBuffer {
+        type Writer = Self;
+
+        fn make_writer(&self) -> Self::Writer {
+            C!(self)
+        }
+    }
+
+    #[tokio::test]
+    // Even at trace level, no token, password, or webhook url ends up in the JSON log
+    async fn test_log_redacted() {
+        let buffer = LogBuffer::default();
+        let _guard =
+            tracing::subscriber::set_default(gen_subscriber(tracing::Level::TRACE, C!(buffer)));
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(
+            200,
+            r#"{"status":1,"request":"abc","receipt":"rcpt","acknowledged":1,"acknowledged_at":1,"acknowledged_by":"user_key","acknowledged_by_device":"iphone","expired":0,"expires_at":1}"#,
+        )
+        .await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![
+            Backend::Pushover(Pushover {
+                token_app: Secret::from("secret_token_app"),
+                token_user: Secret::from("secret_token_user"),
+                online: gen_emergency(),
+                ..gen_pushover(&ok.url)
+            }),
+            Backend::Webhook(Webhook {
+                url: Secret::from(format!("{}/secret_webhook_path", err.url)),
+                method: reqwest::Method::POST,
+                headers: vec![(S!("Authorization"), Secret::from("secret_header"))],
+                body: S!(WEBHOOK_BODY),
+            }),
+        ];
+        app_env.fallbacks = vec![Backend::Discord(Discord {
+            url: Secret::from(format!("{}/api/webhooks/1/secret_discord", err.url)),
+        })];
+
+        tracing::debug!("{app_env:?}");
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        PushRequest::poll_receipts(&app_env, &db).await.unwrap();
+
+        let logs = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert!(logs.contains("[redacted]"));
+        assert!(logs.contains("webhook failed"));
+        assert!(logs.contains("Receipt acknowledged"));
+        for secret in [
+            "secret_token_app",
+            "secret_token_user",
+            "secret_webhook_path",
+            "secret_header",
+            "secret_discord",
+        ] {
+            assert!(!logs.contains(secret), "{secret} found in log");
+        }
+        assert_eq!(ok.requests().len(), 2);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // A running instance holds the lock, the shutdown is still recorded, and the going offline message sent
+    async fn test_run_shutdown_locked() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.location_lock = app_env.location_base.join(format!("{uuid}.lock"));
+        ModelSkipRequest::update(&db, false).await.unwrap();
+
+        let mut lock_file = open_lock_file(&app_env).unwrap();
+        let _guard = lock_file.try_write().unwrap();
+        let mut second_lock_file = open_lock_file(&app_env).unwrap();
+        assert!(second_lock_file.try_write().is_err());
+
+        run_shutdown(&app_env, Some("poweroff")).await.unwrap();
+
+        assert!(ModelShutdown::get_last(&db).await.unwrap().is_some());
+        assert_eq!(ok.requests().len(), 1);
+        assert!(ok.requests()[0].body.contains(r#""event":"offline""#));
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].delivered);
+
+        app_env.rm_lock_file();
+        test_cleanup(uuid, Some(db)).await;
+    }
 }
@@ -0,0 +1,138 @@
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::{C, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+#[derive(Debug, Clone)]
+pub struct Discord {
+    pub url: Secret,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedField {
+    name: &'static str,
+    value: String,
+    inline: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct Embed {
+    title: String,
+    /// The full message text, as only it has the event details, such as the downtime or changed ips
+    description: String,
+    color: u32,
+    fields: Vec<EmbedField>,
+}
+
+/// JSON body for a Discord incoming webhook
+#[derive(Debug, Serialize)]
+struct DiscordBody {
+    username: &'static str,
+    embeds: [Embed; 1],
+}
+
+impl Discord {
+    /// Embed colour, green for online, blue for install, orange for anything else
+    fn color(event: &str) -> u32 {
+        match event {
+            "online" => 0x002e_cc71,
+            "service installed" => 0x0034_98db,
+            _ => 0x00e6_7e22,
+        }
+    }
+
+    fn gen_body(message: &Message) -> DiscordBody {
+        DiscordBody {
+            username: env!("CARGO_PKG_NAME"),
+            embeds: [Embed {
+                title: format!("{} {}", message.machine_name, message.event),
+                description: C!(message.text),
+                color: Self::color(&message.event),
+                fields: message
+                    .fields()
+                    .into_iter()
+                    .map(|(name, value)| EmbedField {
+                        name,
+                        value,
+                        inline: true,
+                    })
+                    .collect(),
+            }],
+        }
+    }
+}
+
+impl Notifier for Discord {
+    fn name(&self) -> &'static str {
+        "discord"
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        client
+            .post(self.url.expose())
+            .json(&Self::gen_body(message))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .error_for_status()
+            .map_err(reqwest::Error::without_url)?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::tests::{StandIn, gen_message};
+
+    #[tokio::test]
+    async fn test_discord_send() {
+        let stand_in = StandIn::start(204, "").await;
+        let discord = Discord {
+            url: Secret::from(format!("{}/api/webhooks/1/token", stand_in.url)),
+        };
+
+        let message = gen_message();
+
+        let result = discord.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/api/webhooks/1/token");
+        let body = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(body["username"], "sysup");
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["title"], "test_machine online");
+        assert_eq!(embed["color"], 0x002e_cc71);
+        assert_eq!(embed["description"], message.text);
+        assert_eq!(embed["fields"].as_array().unwrap().len(), 6);
+        assert_eq!(embed["fields"][1]["name"], "Event");
+        assert_eq!(embed["fields"][1]["value"], "online");
+        assert_eq!(embed["fields"][2]["value"], "192.168.0.10");
+        assert_eq!(embed["fields"][3]["value"], "127.0.0.1");
+        assert_eq!(embed["fields"][4]["value"], "::1");
+        assert_eq!(
+            embed["fields"][5]["value"],
+            "2025-01-01 12:00:00 Europe/London"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_discord_send_err() {
+        let stand_in =
+            StandIn::start(404, r#"{"message": "Unknown Webhook", "code": 10015}"#).await;
+        let discord = Discord {
+            url: Secret::from(C!(stand_in.url)),
+        };
+
+        let result = discord.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+    }
+}
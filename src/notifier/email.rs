@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+use reqwest::Client;
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Subject used when `SMTP_SUBJECT` isn't set
+pub const SMTP_SUBJECT: &str = "{machine_name} {event}";
+
+/// Give up on the smtp server after this many seconds
+const SMTP_TIMEOUT: u64 = 10;
+
+/// How the connection to the smtp server is secured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SmtpSecurity {
+    /// Unencrypted, only suitable for a trusted internal relay
+    Plain,
+    StartTls,
+    /// Implicit TLS, aka SMTPS, normally port 465
+    Tls,
+}
+
+impl SmtpSecurity {
+    pub const fn default_port(self) -> u16 {
+        match self {
+            Self::Plain => 25,
+            Self::StartTls => 587,
+            Self::Tls => 465,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Email {
+    pub host: String,
+    pub port: u16,
+    pub security: SmtpSecurity,
+    /// Username & password, if the server requires authentication
+    pub credentials: Option<(String, Secret)>,
+    pub from: Mailbox,
+    pub to: Vec<Mailbox>,
+    /// Subject template, uses the same placeholders as the webhook body
+    pub subject: String,
+}
+
+impl Email {
+    fn get_transport(&self) -> Result<AsyncSmtpTransport<Tokio1Executor>, AppError> {
+        let mut builder = match self.security {
+            SmtpSecurity::Plain => {
+                AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&self.host)
+            }
+            SmtpSecurity::StartTls => {
+                AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&self.host)?
+            }
+            SmtpSecurity::Tls => AsyncSmtpTransport::<Tokio1Executor>::relay(&self.host)?,
+        }
+        .port(self.port)
+        .timeout(Some(Duration::from_secs(SMTP_TIMEOUT)));
+        if let Some((username, password)) = &self.credentials {
+            builder = builder.credentials(Credentials::new(C!(username), S!(password.expose())));
+        }
+        Ok(builder.build())
+    }
+
+    fn gen_email(&self, message: &Message) -> Result<lettre::Message, AppError> {
+        let mut builder = lettre::Message::builder()
+            .from(C!(self.from))
+            .subject(message.render(&self.subject))
+            .header(ContentType::TEXT_PLAIN);
+        for to in &self.to {
+            builder = builder.to(C!(to));
+        }
+        Ok(builder.body(C!(message.text))?)
+    }
+}
+
+impl Notifier for Email {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    async fn send(&self, _: &Client, message: &Message) -> Result<Sent, AppError> {
+        self.get_transport()?.send(self.gen_email(message)?).await?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+    };
+
+    use super::*;
+    use crate::tests::{gen_email, gen_message};
+
+    /// Minimal local smtp server, records each command and the message data
+    /// Any RCPT TO for a "reject" address is refused
+    struct SmtpSink {
+        port: u16,
+        received: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl SmtpSink {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let received = Arc::new(Mutex::new(vec![]));
+            let captured = Arc::clone(&received);
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let (reader, mut writer) = stream.into_split();
+                    let mut lines = BufReader::new(reader).lines();
+                    writer.write_all(b"220 sink ESMTP\r\n").await.ok();
+                    let mut in_data = false;
+                    while let Ok(Some(line)) = lines.next_line().await {
+                        captured.lock().unwrap().push(C!(line));
+                        let reply: &[u8] = if in_data {
+                            if line != "." {
+                                continue;
+                            }
+                            in_data = false;
+                            b"250 queued\r\n"
+                        } else if line.starts_with("EHLO") {
+                            b"250-sink\r\n250 AUTH PLAIN LOGIN\r\n"
+                        } else if line.starts_with("AUTH") {
+                            b"235 authenticated\r\n"
+                        } else if line.starts_with("RCPT") && line.contains("reject") {
+                            b"550 no such user\r\n"
+                        } else if line == "DATA" {
+                            in_data = true;
+                            b"354 go ahead\r\n"
+                        } else if line == "QUIT" {
+                            writer.write_all(b"221 bye\r\n").await.ok();
+                            break;
+                        } else {
+                            b"250 ok\r\n"
+                        };
+                        writer.write_all(reply).await.ok();
+                    }
+                }
+            });
+            Self { port, received }
+        }
+
+        fn received(&self) -> Vec<String> {
+            self.received.lock().unwrap().clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_email_send() {
+        let sink = SmtpSink::start().await;
+        let email = gen_email(sink.port, &["ops@example.com", "admin@example.com"]);
+
+        let result = email.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_ok());
+        let received = sink.received();
+        assert!(received.contains(&S!("MAIL FROM:<sysup@example.com>")));
+        assert!(received.contains(&S!("RCPT TO:<ops@example.com>")));
+        assert!(received.contains(&S!("RCPT TO:<admin@example.com>")));
+        assert!(received.contains(&S!("Subject: test_machine online")));
+        assert!(
+            received
+                .join("\n")
+                .contains("test_machine online @ 2025-01-01")
+        );
+        assert!(!received.iter().any(|i| i.starts_with("AUTH")));
+    }
+
+    #[tokio::test]
+    async fn test_email_send_credentials() {
+        let sink = SmtpSink::start().await;
+        let mut email = gen_email(sink.port, &["ops@example.com"]);
+        email.credentials = Some((S!("user"), Secret::from("pass")));
+        email.subject = S!("[{event}] {machine_name}");
+
+        let result = email.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_ok());
+        let received = sink.received();
+        assert!(received.iter().any(|i| i.starts_with("AUTH")));
+        assert!(received.contains(&S!("Subject: [online] test_machine")));
+    }
+
+    #[tokio::test]
+    async fn test_email_send_err() {
+        let sink = SmtpSink::start().await;
+        let email = gen_email(sink.port, &["reject@example.com"]);
+
+        let result = email.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), AppError::Smtp(_)));
+    }
+}
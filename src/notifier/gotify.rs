@@ -0,0 +1,171 @@
+use reqwest::Client;
+use serde::Serialize;
+use serde_json::{Value, json};
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+#[derive(Debug, Clone)]
+pub struct Gotify {
+    pub url: String,
+    pub token: Secret,
+    /// Static title, if None then "{machine_name} {event}" is used
+    pub title: Option<String>,
+    /// 0 (min) to 10 (max), escalated messages are always sent as 10
+    pub priority: u8,
+    /// Send the message as markdown, with each detail on its own line
+    pub markdown: bool,
+}
+
+/// JSON body for the gotify create message endpoint
+#[derive(Debug, Serialize)]
+struct GotifyBody {
+    title: String,
+    message: String,
+    priority: u8,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extras: Option<Value>,
+}
+
+impl Gotify {
+    /// Backslash escape the punctuation that has a meaning in markdown
+    fn escape_markdown(input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        for c in input.chars() {
+            if "\\`*_{}[]()<>#+-.!|~".contains(c) {
+                output.push('\\');
+            }
+            output.push(c);
+        }
+        output
+    }
+
+    /// Render the message text, followed by each detail as a markdown list
+    fn gen_markdown(message: &Message) -> String {
+        let ip = |ip: Option<std::net::IpAddr>| ip.map_or_else(|| S!("-"), |i| i.to_string());
+        format!(
+            "**{}** {}\n\n{}\n\n- **time**: {}\n- **local ip**: {}\n- **ipv4**: {}\n- **ipv6**: {}",
+            message.machine_name,
+            message.event,
+            Self::escape_markdown(&message.text),
+            message.timestamp,
+            message.local_ip,
+            ip(message.ipv4),
+            ip(message.ipv6)
+        )
+    }
+
+    fn gen_body(&self, message: &Message) -> GotifyBody {
+        let priority = if message.escalated { 10 } else { self.priority };
+        let title =
+            C!(self.title).unwrap_or_else(|| format!("{} {}", message.machine_name, message.event));
+        if self.markdown {
+            GotifyBody {
+                title,
+                message: Self::gen_markdown(message),
+                priority,
+                extras: Some(json!({"client::display": {"contentType": "text/markdown"}})),
+            }
+        } else {
+            GotifyBody {
+                title,
+                message: C!(message.text),
+                priority,
+                extras: None,
+            }
+        }
+    }
+}
+
+impl Notifier for Gotify {
+    fn name(&self) -> &'static str {
+        "gotify"
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        client
+            .post(format!("{}/message", self.url.trim_end_matches('/')))
+            .header("X-Gotify-Key", self.token.expose())
+            .json(&self.gen_body(message))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{StandIn, gen_gotify, gen_message};
+
+    #[tokio::test]
+    async fn test_gotify_send() {
+        let stand_in = StandIn::start(200, r#"{"id":1}"#).await;
+        let gotify = gen_gotify(&stand_in.url, false);
+        let message = gen_message();
+
+        let result = gotify.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/message");
+        assert_eq!(requests[0].headers["x-gotify-key"], "test_token");
+
+        let body = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(body["title"], "test_machine online");
+        assert_eq!(body["message"], message.text);
+        assert_eq!(body["priority"], 8);
+        assert!(body.get("extras").is_none());
+    }
+
+    #[test]
+    fn test_gotify_body_escalated() {
+        let gotify = gen_gotify("http://127.0.0.1:1", false);
+        let mut message = gen_message();
+        message.escalated = true;
+
+        let result = gotify.gen_body(&message);
+
+        assert_eq!(result.priority, 10);
+    }
+
+    #[tokio::test]
+    async fn test_gotify_send_markdown() {
+        let stand_in = StandIn::start(200, r#"{"id":1}"#).await;
+        let mut gotify = gen_gotify(&stand_in.url, true);
+        gotify.title = Some(S!("sysup"));
+
+        let mut message = gen_message();
+        message.text = S!("test_machine online, last shutdown was unclean, down for *1h*");
+
+        let result = gotify.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let body = serde_json::from_str::<Value>(&stand_in.requests()[0].body).unwrap();
+        assert_eq!(body["title"], "sysup");
+        assert_eq!(
+            body["extras"]["client::display"]["contentType"],
+            "text/markdown"
+        );
+        let message = body["message"].as_str().unwrap();
+        assert!(message.starts_with(
+            "**test_machine** online\n\ntest\\_machine online, last shutdown was unclean, down for \\*1h\\*\n\n"
+        ));
+        assert!(message.contains("- **local ip**: 192.168.0.10"));
+        assert!(message.contains("- **ipv6**: ::1"));
+    }
+
+    #[tokio::test]
+    async fn test_gotify_send_err() {
+        let stand_in = StandIn::start(401, r#"{"error":"Unauthorized"}"#).await;
+        let gotify = gen_gotify(&stand_in.url, false);
+
+        let result = gotify.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use std::net::IpAddr;
+
+use futures_util::future::join_all;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{C, S, app_error::AppError};
+
+mod discord;
+mod email;
+mod gotify;
+mod mqtt;
+mod ntfy;
+mod pushover;
+mod slack;
+mod telegram;
+mod webhook;
+
+pub use discord::Discord;
+pub use email::{Email, SMTP_SUBJECT, SmtpSecurity};
+pub use gotify::Gotify;
+pub use mqtt::{MQTT_TOPIC, Mqtt};
+pub use ntfy::{NTFY_URL, Ntfy, NtfyAuth};
+pub use pushover::{PUSHOVER_URL, Pushover, PushoverFormat, PushoverOptions, Receipt};
+pub use slack::Slack;
+pub use telegram::{ParseMode, TELEGRAM_URL, Telegram};
+pub use webhook::{WEBHOOK_BODY, Webhook};
+
+/// Everything known about a single alert, each notifier renders this into whatever its api requires
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    pub machine_name: String,
+    pub event: String,
+    pub timestamp: String,
+    /// When the message was created, seconds since the unix epoch
+    #[serde(default)]
+    pub unix_timestamp: u64,
+    pub timezone: String,
+    pub local_ip: String,
+    pub ipv4: Option<IpAddr>,
+    pub ipv6: Option<IpAddr>,
+    /// Each reported interface with its addresses, e.g. "eth0 192.168.1.2, wlan0 10.0.0.3"
+    #[serde(default)]
+    pub interfaces: String,
+    /// The full plain text message, e.g. "machine online @ 2025-01-01 00:00:00 UTC ..."
+    pub text: String,
+    /// Sent above the configured priority, e.g. when a boot loop is detected
+    #[serde(default)]
+    pub escalated: bool,
+}
+
+impl Message {
+    /// Each detail of the message as a (name, value) pair, for backends that display structured fields
+    pub fn fields(&self) -> [(&'static str, String); 6] {
+        let ip = |ip: Option<IpAddr>| ip.map_or_else(|| S!("-"), |i| i.to_string());
+        [
+            ("Machine", C!(self.machine_name)),
+            ("Event", C!(self.event)),
+            ("Local IP", C!(self.local_ip)),
+            ("IPv4", ip(self.ipv4)),
+            ("IPv6", ip(self.ipv6)),
+            ("Time", C!(self.timestamp)),
+        ]
+    }
+
+    /// Each template placeholder, and the value it gets replaced with, missing ips become an empty string
+    pub fn placeholders(&self) -> [(&'static str, String); 9] {
+        let ip = |ip: Option<IpAddr>| ip.map_or_else(String::new, |i| i.to_string());
+        [
+            ("{machine_name}", C!(self.machine_name)),
+            ("{event}", C!(self.event)),
+            ("{local_ip}", C!(self.local_ip)),
+            ("{ipv4}", ip(self.ipv4)),
+            ("{ipv6}", ip(self.ipv6)),
+            ("{interfaces}", C!(self.interfaces)),
+            ("{timestamp}", C!(self.timestamp)),
+            ("{timezone}", C!(self.timezone)),
+            ("{message}", C!(self.text)),
+        ]
+    }
+
+    /// Replace every placeholder in a template with its value
+    pub fn render(&self, template: &str) -> String {
+        self.placeholders()
+            .iter()
+            .fold(S!(template), |output, (placeholder, value)| {
+                output.replace(placeholder, value)
+            })
+    }
+}
+
+/// What a backend returned for an accepted message, most don't return anything worth keeping
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Sent {
+    /// The id the backend gave the message
+    pub id: Option<String>,
+    /// Pushover emergency priority receipt, used to check if the message has been acknowledged
+    pub receipt: Option<String>,
+}
+
+/// A backend which is able to deliver a Message
+pub trait Notifier {
+    /// Name of the notifier, used in logs
+    fn name(&self) -> &'static str;
+
+    /// Send the message, should only return Ok when the backend has accepted it
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError>;
+}
+
+/// The notifier backends that can be selected via the `NOTIFIER` env
+/// Only ever created once, at startup, so the size of the Pushover variant doesn't matter
+#[derive(Debug, Clone)]
+#[expect(clippy::large_enum_variant)]
+pub enum Backend {
+    Discord(Discord),
+    Email(Email),
+    Gotify(Gotify),
+    Mqtt(Mqtt),
+    Ntfy(Ntfy),
+    Pushover(Pushover),
+    Slack(Slack),
+    Telegram(Telegram),
+    Webhook(Webhook),
+}
+
+impl Notifier for Backend {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Discord(i) => i.name(),
+            Self::Email(i) => i.name(),
+            Self::Gotify(i) => i.name(),
+            Self::Mqtt(i) => i.name(),
+            Self::Ntfy(i) => i.name(),
+            Self::Pushover(i) => i.name(),
+            Self::Slack(i) => i.name(),
+            Self::Telegram(i) => i.name(),
+            Self::Webhook(i) => i.name(),
+        }
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        match self {
+            Self::Discord(i) => i.send(client, message).await,
+            Self::Email(i) => i.send(client, message).await,
+            Self::Gotify(i) => i.send(client, message).await,
+            Self::Mqtt(i) => i.send(client, message).await,
+            Self::Ntfy(i) => i.send(client, message).await,
+            Self::Pushover(i) => i.send(client, message).await,
+            Self::Slack(i) => i.send(client, message).await,
+            Self::Telegram(i) => i.send(client, message).await,
+            Self::Webhook(i) => i.send(client, message).await,
+        }
+    }
+}
+
+/// The outcome of sending a message via a single notifier
+#[derive(Debug)]
+pub struct Delivery {
+    pub notifier: &'static str,
+    pub result: Result<Sent, AppError>,
+}
+
+/// Send the message via every notifier concurrently, a failure of one doesn't stop the others
+pub async fn send_all(notifiers: &[Backend], client: &Client, message: &Message) -> Vec<Delivery> {
+    join_all(notifiers.iter().map(|notifier| async move {
+        Delivery {
+            notifier: notifier.name(),
+            result: notifier.send(client, message).await,
+        }
+    }))
+    .await
+}
+
+/// Try each fallback in order, stopping at the first one that delivers the message
+pub async fn send_fallback(
+    fallbacks: &[Backend],
+    client: &Client,
+    message: &Message,
+) -> Vec<Delivery> {
+    let mut deliveries = vec![];
+    for notifier in fallbacks {
+        let delivery = Delivery {
+            notifier: notifier.name(),
+            result: notifier.send(client, message).await,
+        };
+        let delivered = delivery.result.is_ok();
+        deliveries.push(delivery);
+        if delivered {
+            break;
+        }
+    }
+    deliveries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::secret::Secret;
+    use crate::tests::{StandIn, gen_message, gen_webhook};
+
+    #[tokio::test]
+    async fn test_notifier_send_all() {
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        let notifiers = [
+            Backend::Discord(Discord {
+                url: Secret::from(C!(err.url)),
+            }),
+            Backend::Webhook(gen_webhook(&ok.url)),
+        ];
+
+        let result = send_all(&notifiers, &Client::new(), &gen_message()).await;
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].notifier, "discord");
+        assert!(result[0].result.is_err());
+        assert_eq!(result[1].notifier, "webhook");
+        assert!(result[1].result.is_ok());
+        assert_eq!(ok.requests().len(), 1);
+        assert_eq!(err.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_notifier_send_fallback() {
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        let fallbacks = [
+            Backend::Discord(Discord {
+                url: Secret::from(C!(err.url)),
+            }),
+            Backend::Webhook(gen_webhook(&ok.url)),
+            Backend::Slack(Slack {
+                url: Secret::from(C!(ok.url)),
+            }),
+        ];
+
+        let result = send_fallback(&fallbacks, &Client::new(), &gen_message()).await;
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].notifier, "discord");
+        assert!(result[0].result.is_err());
+        assert_eq!(result[1].notifier, "webhook");
+        assert!(result[1].result.is_ok());
+        assert_eq!(ok.requests().len(), 1);
+
+        let result = send_fallback(&[], &Client::new(), &gen_message()).await;
+        assert!(result.is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+use std::time::Duration;
+
+use reqwest::Client;
+use rumqttc::{AsyncClient, Event, MqttOptions, Outgoing, Packet, QoS, Transport};
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Topic prefix used when `MQTT_TOPIC` isn't set
+pub const MQTT_TOPIC: &str = "sysup";
+
+/// Give up on the broker after this many seconds
+const MQTT_TIMEOUT: u64 = 10;
+
+#[derive(Debug, Clone)]
+pub struct Mqtt {
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    /// Username & password, if the broker requires authentication
+    pub credentials: Option<(String, Secret)>,
+    /// Prefix, messages are published to `{topic}/{machine_name}/event`
+    pub topic: String,
+    pub qos: QoS,
+}
+
+impl Mqtt {
+    fn get_options(&self, message: &Message) -> MqttOptions {
+        let mut options = MqttOptions::new(
+            format!("{}-{}", env!("CARGO_PKG_NAME"), message.machine_name),
+            C!(self.host),
+            self.port,
+        );
+        options.set_keep_alive(Duration::from_secs(MQTT_TIMEOUT));
+        if let Some((username, password)) = &self.credentials {
+            options.set_credentials(C!(username), S!(password.expose()));
+        }
+        if self.tls {
+            options.set_transport(Transport::tls_with_default_config());
+        }
+        options
+    }
+
+    /// Every event goes to `/event`, an online event is also retained at `/last_boot`
+    fn gen_topics(&self, message: &Message) -> Vec<(String, bool)> {
+        let base = format!(
+            "{}/{}",
+            self.topic.trim_end_matches('/'),
+            message.machine_name
+        );
+        let mut topics = vec![(format!("{base}/event"), false)];
+        if message.event == "online" {
+            topics.push((format!("{base}/last_boot"), true));
+        }
+        topics
+    }
+
+    /// Drive the event loop until every publish has been acknowledged (or sent, for QoS 0), then disconnect
+    async fn publish(&self, message: &Message) -> Result<(), AppError> {
+        let (client, mut event_loop) = AsyncClient::new(self.get_options(message), 10);
+        let payload = serde_json::to_vec(message)?;
+        let topics = self.gen_topics(message);
+        let mut pending = topics.len();
+        for (topic, retain) in topics {
+            client.publish(topic, self.qos, retain, C!(payload)).await?;
+        }
+
+        loop {
+            let event = event_loop
+                .poll()
+                .await
+                .map_err(|e| AppError::MqttConnection(Box::new(e)))?;
+            let delivered = match event {
+                Event::Incoming(Packet::PubAck(_) | Packet::PubComp(_)) => {
+                    self.qos != QoS::AtMostOnce
+                }
+                Event::Outgoing(Outgoing::Publish(_)) => self.qos == QoS::AtMostOnce,
+                Event::Outgoing(Outgoing::Disconnect) => return Ok(()),
+                _ => false,
+            };
+            if delivered {
+                pending = pending.saturating_sub(1);
+                if pending == 0 {
+                    client.try_disconnect()?;
+                }
+            }
+        }
+    }
+}
+
+impl Notifier for Mqtt {
+    fn name(&self) -> &'static str {
+        "mqtt"
+    }
+
+    async fn send(&self, _: &Client, message: &Message) -> Result<Sent, AppError> {
+        tokio::time::timeout(Duration::from_secs(MQTT_TIMEOUT), self.publish(message)).await??;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::{TcpListener, TcpStream},
+    };
+
+    use super::*;
+    use crate::tests::{gen_message, gen_mqtt};
+
+    #[derive(Debug, Clone)]
+    struct Published {
+        topic: String,
+        qos: u8,
+        retain: bool,
+        payload: Vec<u8>,
+    }
+
+    /// Minimal local MQTT 3.1.1 broker, acknowledges everything and records each publish
+    /// A CONNECT with the username "reject" is refused
+    struct BrokerStandIn {
+        port: u16,
+        published: Arc<Mutex<Vec<Published>>>,
+    }
+
+    impl BrokerStandIn {
+        async fn start() -> Self {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let published = Arc::new(Mutex::new(vec![]));
+            let captured = Arc::clone(&published);
+            tokio::spawn(async move {
+                while let Ok((mut stream, _)) = listener.accept().await {
+                    while let Some((header, body)) = Self::read_packet(&mut stream).await {
+                        let reply = match header >> 4 {
+                            // CONNECT
+                            1 => {
+                                if body.windows(6).any(|w| w == b"reject") {
+                                    vec![0x20, 2, 0, 5]
+                                } else {
+                                    vec![0x20, 2, 0, 0]
+                                }
+                            }
+                            // PUBLISH
+                            3 => {
+                                let qos = (header >> 1) & 3;
+                                let topic_len = usize::from(u16::from_be_bytes([body[0], body[1]]));
+                                let topic =
+                                    String::from_utf8_lossy(&body[2..2 + topic_len]).to_string();
+                                let mut offset = 2 + topic_len;
+                                let packet_id = if qos > 0 {
+                                    offset += 2;
+                                    vec![body[offset - 2], body[offset - 1]]
+                                } else {
+                                    vec![]
+                                };
+                                captured.lock().unwrap().push(Published {
+                                    topic,
+                                    qos,
+                                    retain: header & 1 == 1,
+                                    payload: body[offset..].to_vec(),
+                                });
+                                match qos {
+                                    1 => [vec![0x40, 2], packet_id].concat(),
+                                    2 => [vec![0x50, 2], packet_id].concat(),
+                                    _ => vec![],
+                                }
+                            }
+                            // PUBREL
+                            6 => [vec![0x70, 2], body].concat(),
+                            // PINGREQ
+                            12 => vec![0xd0, 0],
+                            _ => break,
+                        };
+                        stream.write_all(&reply).await.ok();
+                    }
+                }
+            });
+            Self { port, published }
+        }
+
+        /// Read a single packet, the fixed header byte and then the remaining length bytes
+        async fn read_packet(stream: &mut TcpStream) -> Option<(u8, Vec<u8>)> {
+            let header = stream.read_u8().await.ok()?;
+            let mut length = 0usize;
+            let mut shift = 0;
+            loop {
+                let byte = stream.read_u8().await.ok()?;
+                length += usize::from(byte & 0x7f) << shift;
+                if byte & 0x80 == 0 {
+                    break;
+                }
+                shift += 7;
+            }
+            let mut body = vec![0; length];
+            stream.read_exact(&mut body).await.ok()?;
+            Some((header, body))
+        }
+
+        /// QoS 0 publishes aren't acknowledged, so give the stand-in a moment to read them
+        async fn published(&self, count: usize) -> Vec<Published> {
+            for _ in 0..20 {
+                if self.published.lock().unwrap().len() >= count {
+                    break;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+            self.published.lock().unwrap().clone()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_send() {
+        for qos in [QoS::AtMostOnce, QoS::AtLeastOnce, QoS::ExactlyOnce] {
+            let broker = BrokerStandIn::start().await;
+            let mqtt = gen_mqtt(broker.port, qos);
+            let message = gen_message();
+
+            let result = mqtt.send(&Client::new(), &message).await;
+
+            assert!(result.is_ok());
+            let published = broker.published(2).await;
+            assert_eq!(published.len(), 2);
+            assert_eq!(published[0].topic, "sysup/test_machine/event");
+            assert_eq!(published[0].qos, qos as u8);
+            assert!(!published[0].retain);
+            assert_eq!(published[1].topic, "sysup/test_machine/last_boot");
+            assert!(published[1].retain);
+
+            let payload =
+                serde_json::from_slice::<serde_json::Value>(&published[0].payload).unwrap();
+            assert_eq!(payload["machine_name"], "test_machine");
+            assert_eq!(payload["event"], "online");
+            assert_eq!(payload["ipv4"], "127.0.0.1");
+            assert_eq!(payload["text"], message.text);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_send_service() {
+        let broker = BrokerStandIn::start().await;
+        let mqtt = gen_mqtt(broker.port, QoS::AtLeastOnce);
+        let mut message = gen_message();
+        message.event = S!("service installed");
+
+        let result = mqtt.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let published = broker.published(1).await;
+        assert_eq!(published.len(), 1);
+        assert_eq!(published[0].topic, "sysup/test_machine/event");
+    }
+
+    #[tokio::test]
+    async fn test_mqtt_send_err() {
+        let broker = BrokerStandIn::start().await;
+        let mut mqtt = gen_mqtt(broker.port, QoS::AtLeastOnce);
+        mqtt.credentials = Some((S!("reject"), Secret::from("password")));
+
+        let result = mqtt.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        assert!(matches!(result.unwrap_err(), AppError::MqttConnection(_)));
+        assert!(broker.published(0).await.is_empty());
+    }
+}
@@ -0,0 +1,146 @@
+use reqwest::Client;
+use serde::Serialize;
+
+use crate::{app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Public ntfy server, used when `NTFY_URL` isn't set
+pub const NTFY_URL: &str = "https://ntfy.sh";
+
+/// Either an access token, or a username & password
+#[derive(Debug, Clone)]
+pub enum NtfyAuth {
+    Token(Secret),
+    Basic { username: String, password: Secret },
+}
+
+#[derive(Debug, Clone)]
+pub struct Ntfy {
+    pub url: String,
+    pub topic: String,
+    pub auth: Option<NtfyAuth>,
+    /// 1 (min) to 5 (max), 3 is the ntfy default, escalated messages are always sent as 5
+    pub priority: u8,
+    pub tags: Vec<String>,
+}
+
+/// JSON body for publishing to the root of a ntfy server
+#[derive(Debug, Serialize)]
+struct NtfyBody<'a> {
+    topic: &'a str,
+    title: String,
+    message: &'a str,
+    priority: u8,
+    tags: &'a [String],
+}
+
+impl Ntfy {
+    fn gen_body<'a>(&'a self, message: &'a Message) -> NtfyBody<'a> {
+        NtfyBody {
+            topic: &self.topic,
+            title: format!("{} {}", message.machine_name, message.event),
+            message: &message.text,
+            priority: if message.escalated { 5 } else { self.priority },
+            tags: &self.tags,
+        }
+    }
+}
+
+impl Notifier for Ntfy {
+    fn name(&self) -> &'static str {
+        "ntfy"
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        let mut request = client
+            .post(self.url.trim_end_matches('/'))
+            .json(&self.gen_body(message));
+        request = match &self.auth {
+            Some(NtfyAuth::Token(token)) => request.bearer_auth(token.expose()),
+            Some(NtfyAuth::Basic { username, password }) => {
+                request.basic_auth(username, Some(password.expose()))
+            }
+            None => request,
+        };
+        request.send().await?.error_for_status()?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        S,
+        tests::{StandIn, gen_message, gen_ntfy},
+    };
+
+    #[tokio::test]
+    async fn test_ntfy_send_token() {
+        let stand_in = StandIn::start(200, r#"{"id":"abc"}"#).await;
+        let ntfy = gen_ntfy(
+            &stand_in.url,
+            Some(NtfyAuth::Token(Secret::from("tk_test"))),
+        );
+        let message = gen_message();
+
+        let result = ntfy.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/");
+        assert_eq!(requests[0].headers["authorization"], "Bearer tk_test");
+
+        let body = serde_json::from_str::<serde_json::Value>(&requests[0].body).unwrap();
+        assert_eq!(body["topic"], "test_topic");
+        assert_eq!(body["title"], "test_machine online");
+        assert_eq!(body["message"], message.text);
+        assert_eq!(body["priority"], 4);
+        assert_eq!(body["tags"], serde_json::json!(["computer", "sysup"]));
+    }
+
+    #[test]
+    fn test_ntfy_body_escalated() {
+        let ntfy = gen_ntfy(NTFY_URL, None);
+        let mut message = gen_message();
+        message.escalated = true;
+
+        let result = ntfy.gen_body(&message);
+
+        assert_eq!(result.priority, 5);
+    }
+
+    #[tokio::test]
+    async fn test_ntfy_send_basic() {
+        let stand_in = StandIn::start(200, r#"{"id":"abc"}"#).await;
+        let ntfy = gen_ntfy(
+            &stand_in.url,
+            Some(NtfyAuth::Basic {
+                username: S!("user"),
+                password: Secret::from("pass"),
+            }),
+        );
+
+        let result = ntfy.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        // base64 of "user:pass"
+        assert_eq!(requests[0].headers["authorization"], "Basic dXNlcjpwYXNz");
+    }
+
+    #[tokio::test]
+    async fn test_ntfy_send_err() {
+        let stand_in = StandIn::start(403, r#"{"code":40301,"error":"forbidden"}"#).await;
+        let ntfy = gen_ntfy(&stand_in.url, None);
+
+        let result = ntfy.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        assert!(!stand_in.requests()[0].headers.contains_key("authorization"));
+    }
+}
@@ -0,0 +1,577 @@
+use reqwest::{Client, StatusCode, header::HeaderMap};
+use serde::Deserialize;
+use url::Url;
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Pushover api url, used when `PUSHOVER_URL` isn't set
+pub const PUSHOVER_URL: &str = "https://api.pushover.net";
+
+type Params = Vec<(&'static str, String)>;
+
+/// How pushover should display the message text
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PushoverFormat {
+    #[default]
+    Plain,
+    Html,
+    Monospace,
+}
+
+/// The optional message settings, each event type can use different ones
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PushoverOptions {
+    /// Title template, uses the same placeholders as the webhook body, pushover uses the app name when None
+    pub title: Option<String>,
+    /// -2 (lowest) to 2 (emergency)
+    pub priority: i8,
+    /// Seconds between each repeat of an emergency priority message, at least 30
+    pub retry: u64,
+    /// Seconds until an emergency priority message stops repeating, at most 10800
+    pub expire: u64,
+    pub sound: Option<String>,
+    /// Only send to these devices, every device when empty
+    pub devices: Vec<String>,
+    pub url: Option<String>,
+    pub url_title: Option<String>,
+    pub format: PushoverFormat,
+    /// Seconds until the message is deleted from the device
+    pub ttl: Option<u64>,
+    /// Display the time the event happened, rather than when pushover received it
+    pub timestamp: bool,
+}
+
+/// Response from pushover api, a `status` of anything other than 1 means the message wasn't accepted, with the reasons in `errors`
+#[derive(Debug, Deserialize)]
+struct PushoverResponse {
+    status: u8,
+    request: String,
+    /// Only returned for emergency priority messages
+    receipt: Option<String>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// Response from the receipts api, the timestamps are 0 until the event has happened
+#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+pub struct Receipt {
+    #[serde(deserialize_with = "from_int")]
+    pub acknowledged: bool,
+    pub acknowledged_at: u64,
+    /// User key of whoever acknowledged it
+    pub acknowledged_by: String,
+    pub acknowledged_by_device: String,
+    #[serde(deserialize_with = "from_int")]
+    pub expired: bool,
+    pub expires_at: u64,
+}
+
+/// Pushover uses 0 & 1 for booleans
+fn from_int<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(u8::deserialize(deserializer)? == 1)
+}
+
+/// The monthly message limit of the app, from the `X-Limit-App-*` headers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Limit {
+    remaining: u64,
+    /// Unix timestamp of when the limit is reset
+    reset: u64,
+}
+
+impl Limit {
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let header = |key: &str| {
+            headers
+                .get(key)
+                .and_then(|i| i.to_str().ok())
+                .and_then(|i| i.parse::<u64>().ok())
+        };
+        Some(Self {
+            remaining: header("X-Limit-App-Remaining")?,
+            reset: header("X-Limit-App-Reset")?,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Pushover {
+    pub url: String,
+    pub token_app: Secret,
+    pub token_user: Secret,
+    /// Used for online, and boot loop, events
+    pub online: PushoverOptions,
+    /// Used for service install & uninstall events
+    pub service: PushoverOptions,
+    /// Used for every other event, e.g. heartbeat, offline, ip & interface changes, and rate limit summaries
+    pub status: PushoverOptions,
+}
+
+impl Pushover {
+    /// The options for the event, and the highest priority it can be sent at
+    /// Status events are never emergency priority, as each would leave a receipt to poll
+    fn get_options(&self, message: &Message) -> (&PushoverOptions, i8) {
+        match message.event.as_str() {
+            "online" | "boot loop" => (&self.online, 2),
+            "service installed" | "service uninstalled" => (&self.service, 2),
+            _ => (&self.status, 1),
+        }
+    }
+
+    /// Generate the params, aka the message, optional settings are only included when set
+    fn gen_params(&self, message: &Message) -> Params {
+        let (options, max_priority) = self.get_options(message);
+        // An escalated message is at least high priority
+        let priority = if message.escalated {
+            options.priority.max(1)
+        } else {
+            options.priority
+        }
+        .min(max_priority);
+        let mut params = vec![
+            ("token", S!(self.token_app.expose())),
+            ("user", S!(self.token_user.expose())),
+            ("message", C!(message.text)),
+            ("priority", priority.to_string()),
+        ];
+        if priority == 2 {
+            params.push(("retry", options.retry.to_string()));
+            params.push(("expire", options.expire.to_string()));
+        }
+        if let Some(title) = &options.title {
+            params.push(("title", message.render(title)));
+        }
+        if let Some(sound) = &options.sound {
+            params.push(("sound", C!(sound)));
+        }
+        if !options.devices.is_empty() {
+            params.push(("device", options.devices.join(",")));
+        }
+        if let Some(url) = &options.url {
+            params.push(("url", C!(url)));
+        }
+        if let Some(url_title) = &options.url_title {
+            params.push(("url_title", C!(url_title)));
+        }
+        match options.format {
+            PushoverFormat::Plain => (),
+            PushoverFormat::Html => params.push(("html", S!("1"))),
+            PushoverFormat::Monospace => params.push(("monospace", S!("1"))),
+        }
+        if let Some(ttl) = options.ttl {
+            params.push(("ttl", ttl.to_string()));
+        }
+        if options.timestamp && message.unix_timestamp > 0 {
+            params.push(("timestamp", message.unix_timestamp.to_string()));
+        }
+        params
+    }
+
+    /// Turn the http status, limit headers, and body, into either the request id or a typed error
+    /// An accepted message is always delivered, even when it used up the last of the app limit
+    fn parse_response(
+        status: StatusCode,
+        limit: Option<Limit>,
+        body: Option<PushoverResponse>,
+    ) -> Result<Sent, AppError> {
+        match body {
+            Some(body) if status.is_success() && body.status == 1 => {
+                if let Some(limit) = limit.filter(|i| i.remaining == 0) {
+                    tracing::warn!("pushover app limit reached, resets at {}", limit.reset);
+                }
+                Ok(Sent {
+                    id: Some(body.request),
+                    receipt: body.receipt,
+                })
+            }
+            _ if status == StatusCode::TOO_MANY_REQUESTS
+                || limit.is_some_and(|i| i.remaining == 0) =>
+            {
+                let limit = limit.unwrap_or(Limit {
+                    remaining: 0,
+                    reset: 0,
+                });
+                Err(AppError::PushoverLimit(limit.remaining, limit.reset))
+            }
+            Some(body) => Err(AppError::Pushover(status.as_u16(), body.errors.join(", "))),
+            None => Err(AppError::Pushover(status.as_u16(), S!("invalid response"))),
+        }
+    }
+
+    /// Check if an emergency priority message has been acknowledged
+    /// The receipts api only accepts the app token in the url, so it's removed from any reqwest error
+    pub async fn check_receipt(&self, client: &Client, receipt: &str) -> Result<Receipt, AppError> {
+        let url = Url::parse_with_params(
+            &format!(
+                "{}/1/receipts/{receipt}.json",
+                self.url.trim_end_matches('/')
+            ),
+            [("token", self.token_app.expose())],
+        )?;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        let status = response.status();
+        if !status.is_success() {
+            let errors = response
+                .json::<PushoverResponse>()
+                .await
+                .map(|i| i.errors.join(", "))
+                .unwrap_or_default();
+            return Err(AppError::Pushover(status.as_u16(), errors));
+        }
+        Ok(response
+            .json::<Receipt>()
+            .await
+            .map_err(reqwest::Error::without_url)?)
+    }
+}
+
+impl Notifier for Pushover {
+    fn name(&self) -> &'static str {
+        "pushover"
+    }
+
+    /// Returns the pushover request id, and receipt, the params are sent as a form, so the tokens aren't in the url
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        let response = client
+            .post(format!(
+                "{}/1/messages.json",
+                self.url.trim_end_matches('/')
+            ))
+            .form(&self.gen_params(message))
+            .send()
+            .await?;
+        let status = response.status();
+        let limit = Limit::from_headers(response.headers());
+        if let Some(limit) = limit {
+            tracing::debug!("pushover {} messages remaining", limit.remaining);
+        }
+        let body = response.json::<PushoverResponse>().await.ok();
+        Self::parse_response(status, limit, body)
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::tests::{StandIn, gen_emergency, gen_message, gen_pushover};
+
+    #[test]
+    fn test_pushover_generate_params() {
+        let pushover = gen_pushover(PUSHOVER_URL);
+        let message = gen_message();
+
+        let result = pushover.gen_params(&message);
+
+        assert_eq!(result[0], ("token", S!("test_token_app")));
+        assert_eq!(result[1], ("user", S!("test_token_user")));
+        assert_eq!(result[2], ("message", C!(message.text)));
+        assert_eq!(result[3], ("priority", S!("0")));
+        assert_eq!(result.len(), 4);
+    }
+
+    #[test]
+    fn test_pushover_generate_params_escalated() {
+        let mut pushover = gen_pushover(PUSHOVER_URL);
+        let mut message = gen_message();
+        message.escalated = true;
+
+        let result = pushover.gen_params(&message);
+        assert_eq!(result[3], ("priority", S!("1")));
+
+        // Never lowered
+        pushover.online.priority = 2;
+        pushover.online.retry = 30;
+        pushover.online.expire = 60;
+        let result = pushover.gen_params(&message);
+        assert_eq!(
+            result[3..],
+            [
+                ("priority", S!("2")),
+                ("retry", S!("30")),
+                ("expire", S!("60"))
+            ]
+        );
+    }
+
+    #[test]
+    /// Status events use their own options, and are never sent at emergency priority, even when escalated
+    fn test_pushover_generate_params_status() {
+        let mut pushover = gen_pushover(PUSHOVER_URL);
+        pushover.online = gen_emergency();
+        pushover.service = gen_emergency();
+        pushover.status.sound = Some(S!("none"));
+        let mut message = gen_message();
+
+        for event in [
+            "heartbeat",
+            "offline",
+            "ip changed",
+            "interface changed",
+            "suppressed",
+        ] {
+            message.event = S!(event);
+            let result = pushover.gen_params(&message);
+            assert_eq!(
+                result[3..],
+                [("priority", S!("0")), ("sound", S!("none"))],
+                "{event}"
+            );
+        }
+
+        pushover.status = gen_emergency();
+        for event in ["heartbeat", "offline"] {
+            message.event = S!(event);
+            message.escalated = true;
+            let result = pushover.gen_params(&message);
+            assert_eq!(result[3], ("priority", S!("1")));
+            assert!(
+                !result
+                    .iter()
+                    .any(|(key, _)| *key == "retry" || *key == "expire")
+            );
+        }
+
+        for event in ["online", "boot loop", "service installed"] {
+            message.event = S!(event);
+            let result = pushover.gen_params(&message);
+            assert_eq!(result[3], ("priority", S!("2")), "{event}");
+        }
+    }
+
+    #[test]
+    fn test_pushover_generate_params_options() {
+        let mut pushover = gen_pushover(PUSHOVER_URL);
+        pushover.online = PushoverOptions {
+            title: Some(S!("{machine_name} is {event}")),
+            priority: 1,
+            retry: 30,
+            expire: 60,
+            sound: Some(S!("cosmic")),
+            devices: vec![S!("phone"), S!("tablet")],
+            url: Some(S!("https://example.com")),
+            url_title: Some(S!("dashboard")),
+            format: PushoverFormat::Html,
+            ttl: Some(3600),
+            timestamp: true,
+        };
+        pushover.service = PushoverOptions {
+            priority: -1,
+            format: PushoverFormat::Monospace,
+            ..PushoverOptions::default()
+        };
+        let mut message = gen_message();
+
+        let result = pushover.gen_params(&message);
+
+        assert_eq!(
+            result[3..],
+            [
+                ("priority", S!("1")),
+                ("title", S!("test_machine is online")),
+                ("sound", S!("cosmic")),
+                ("device", S!("phone,tablet")),
+                ("url", S!("https://example.com")),
+                ("url_title", S!("dashboard")),
+                ("html", S!("1")),
+                ("ttl", S!("3600")),
+                ("timestamp", S!("1735732800")),
+            ]
+        );
+
+        message.event = S!("service installed");
+
+        let result = pushover.gen_params(&message);
+
+        assert_eq!(
+            result[3..],
+            [("priority", S!("-1")), ("monospace", S!("1"))]
+        );
+    }
+
+    #[test]
+    fn test_pushover_parse_response() {
+        let body = |status: u8, errors: &[&str]| {
+            Some(PushoverResponse {
+                status,
+                request: S!("abc"),
+                receipt: None,
+                errors: errors.iter().map(|i| S!(*i)).collect(),
+            })
+        };
+        let limit = Some(Limit {
+            remaining: 10,
+            reset: 1_700_000_000,
+        });
+
+        let result = Pushover::parse_response(StatusCode::OK, limit, body(1, &[]));
+        assert_eq!(result.unwrap().id, Some(S!("abc")));
+
+        let result = Pushover::parse_response(StatusCode::OK, None, body(0, &["oops"]));
+        assert!(matches!(result, Err(AppError::Pushover(200, _))));
+
+        let result = Pushover::parse_response(StatusCode::BAD_REQUEST, None, None);
+        assert!(matches!(result, Err(AppError::Pushover(400, _))));
+
+        let result = Pushover::parse_response(StatusCode::TOO_MANY_REQUESTS, None, None);
+        assert!(matches!(result, Err(AppError::PushoverLimit(0, 0))));
+
+        let limit = Some(Limit {
+            remaining: 0,
+            reset: 1_700_000_000,
+        });
+        // The last message of the limit was still accepted, so it isn't sent again
+        let result = Pushover::parse_response(StatusCode::OK, limit, body(1, &[]));
+        assert_eq!(result.unwrap().id, Some(S!("abc")));
+
+        let result = Pushover::parse_response(StatusCode::BAD_REQUEST, limit, body(0, &["oops"]));
+        assert!(matches!(
+            result,
+            Err(AppError::PushoverLimit(0, 1_700_000_000))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pushover_send() {
+        let stand_in = StandIn::start(
+            200,
+            r#"{"status":1,"request":"647d2300-702c-4b38-8b2f-d56326ae460b"}"#,
+        )
+        .await;
+        let pushover = gen_pushover(&stand_in.url);
+
+        let result = pushover.send(&Client::new(), &gen_message()).await;
+
+        assert_eq!(
+            result.unwrap(),
+            Sent {
+                id: Some(S!("647d2300-702c-4b38-8b2f-d56326ae460b")),
+                receipt: None,
+            }
+        );
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].path, "/1/messages.json");
+        assert_eq!(
+            requests[0].headers["content-type"],
+            "application/x-www-form-urlencoded"
+        );
+        assert!(requests[0].body.contains("token=test_token_app"));
+        assert!(requests[0].body.contains("user=test_token_user"));
+        assert!(requests[0].body.contains("message=test_machine+online"));
+    }
+
+    #[tokio::test]
+    async fn test_pushover_send_err() {
+        let stand_in = StandIn::start(
+            400,
+            r#"{"user":"invalid","errors":["user identifier is invalid"],"status":0,"request":"5042853c"}"#,
+        )
+        .await;
+        let pushover = gen_pushover(&stand_in.url);
+
+        let result = pushover.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        let result = result.unwrap_err();
+        assert!(matches!(result, AppError::Pushover(400, _)));
+        assert_eq!(
+            result.to_string(),
+            "Pushover error 400: user identifier is invalid"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pushover_send_limit() {
+        let stand_in = StandIn::start_with_headers(
+            429,
+            &[
+                ("X-Limit-App-Limit", "10000"),
+                ("X-Limit-App-Remaining", "0"),
+                ("X-Limit-App-Reset", "1393653600"),
+            ],
+            r#"{"status":0,"request":"abc","errors":["application is over its quota"]}"#,
+        )
+        .await;
+        let pushover = gen_pushover(&stand_in.url);
+
+        let result = pushover.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        let result = result.unwrap_err();
+        assert!(matches!(result, AppError::PushoverLimit(0, 1_393_653_600)));
+        assert_eq!(
+            result.to_string(),
+            "Pushover limit reached, 0 remaining, resets at 1393653600"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pushover_send_emergency() {
+        let stand_in =
+            StandIn::start(200, r#"{"status":1,"request":"abc","receipt":"rcpt"}"#).await;
+        let mut pushover = gen_pushover(&stand_in.url);
+        pushover.online = PushoverOptions {
+            priority: 2,
+            retry: 60,
+            expire: 3600,
+            ..PushoverOptions::default()
+        };
+
+        let result = pushover.send(&Client::new(), &gen_message()).await;
+
+        assert_eq!(result.unwrap().receipt, Some(S!("rcpt")));
+        let body = &stand_in.requests()[0].body;
+        assert!(body.contains("priority=2"));
+        assert!(body.contains("retry=60"));
+        assert!(body.contains("expire=3600"));
+    }
+
+    #[tokio::test]
+    async fn test_pushover_check_receipt() {
+        let stand_in = StandIn::start(
+            200,
+            r#"{"status":1,"acknowledged":1,"acknowledged_at":1360019238,"acknowledged_by":"uQiRzpo4DXghDmr9QzzfQu27cmVRsG","acknowledged_by_device":"iphone","last_delivered_at":1360001238,"expired":0,"expires_at":1360019290,"called_back":0,"called_back_at":0,"request":"abc"}"#,
+        )
+        .await;
+        let pushover = gen_pushover(&stand_in.url);
+
+        let result = pushover.check_receipt(&Client::new(), "rcpt").await;
+
+        assert_eq!(
+            result.unwrap(),
+            Receipt {
+                acknowledged: true,
+                acknowledged_at: 1_360_019_238,
+                acknowledged_by: S!("uQiRzpo4DXghDmr9QzzfQu27cmVRsG"),
+                acknowledged_by_device: S!("iphone"),
+                expired: false,
+                expires_at: 1_360_019_290,
+            }
+        );
+        let requests = stand_in.requests();
+        assert_eq!(requests[0].method, "GET");
+        assert_eq!(
+            requests[0].path,
+            "/1/receipts/rcpt.json?token=test_token_app"
+        );
+
+        let stand_in = StandIn::start(
+            404,
+            r#"{"receipt":"not found","errors":["receipt not found; may be invalid or expired"],"status":0,"request":"abc"}"#,
+        )
+        .await;
+        let pushover = gen_pushover(&stand_in.url);
+
+        let result = pushover.check_receipt(&Client::new(), "rcpt").await;
+
+        assert!(matches!(result, Err(AppError::Pushover(404, _))));
+    }
+}
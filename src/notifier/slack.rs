@@ -0,0 +1,112 @@
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use crate::{app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+#[derive(Debug, Clone)]
+pub struct Slack {
+    pub url: Secret,
+}
+
+impl Slack {
+    /// Block kit body, a header, the message text, then a section of fields, `text` is used for notifications
+    /// The message text is shown as plain text, as it's the only place the event details appear
+    fn gen_body(message: &Message) -> Value {
+        let fields = message
+            .fields()
+            .into_iter()
+            .map(|(name, value)| json!({"type": "mrkdwn", "text": format!("*{name}*\n{value}")}))
+            .collect::<Vec<_>>();
+        json!({
+            "text": message.text,
+            "blocks": [
+                {
+                    "type": "header",
+                    "text": {
+                        "type": "plain_text",
+                        "text": format!("{} {}", message.machine_name, message.event)
+                    }
+                },
+                {
+                    "type": "section",
+                    "text": {
+                        "type": "plain_text",
+                        "text": message.text
+                    }
+                },
+                {
+                    "type": "section",
+                    "fields": fields
+                }
+            ]
+        })
+    }
+}
+
+impl Notifier for Slack {
+    fn name(&self) -> &'static str {
+        "slack"
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        client
+            .post(self.url.expose())
+            .json(&Self::gen_body(message))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .error_for_status()
+            .map_err(reqwest::Error::without_url)?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::{
+        C,
+        tests::{StandIn, gen_message},
+    };
+
+    #[tokio::test]
+    async fn test_slack_send() {
+        let stand_in = StandIn::start(200, "ok").await;
+        let slack = Slack {
+            url: Secret::from(format!("{}/services/T000/B000/XXXX", stand_in.url)),
+        };
+        let message = gen_message();
+
+        let result = slack.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/services/T000/B000/XXXX");
+        let body = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(body["text"], message.text);
+        assert_eq!(body["blocks"][0]["text"]["text"], "test_machine online");
+        assert_eq!(body["blocks"][1]["text"]["type"], "plain_text");
+        assert_eq!(body["blocks"][1]["text"]["text"], message.text);
+        let fields = body["blocks"][2]["fields"].as_array().unwrap();
+        assert_eq!(fields.len(), 6);
+        assert_eq!(fields[0]["text"], "*Machine*\ntest_machine");
+        assert_eq!(fields[1]["text"], "*Event*\nonline");
+        assert_eq!(fields[3]["text"], "*IPv4*\n127.0.0.1");
+    }
+
+    #[tokio::test]
+    async fn test_slack_send_err() {
+        let stand_in = StandIn::start(400, "invalid_payload").await;
+        let slack = Slack {
+            url: Secret::from(C!(stand_in.url)),
+        };
+
+        let result = slack.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use std::net::IpAddr;
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Telegram Bot api url, used when `TELEGRAM_URL` isn't set
+pub const TELEGRAM_URL: &str = "https://api.telegram.org";
+
+/// How the message text should be formatted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    Plain,
+    Html,
+    MarkdownV2,
+}
+
+impl ParseMode {
+    /// The `parse_mode` value that telegram expects, plain text doesn't set one
+    const fn get(self) -> Option<&'static str> {
+        match self {
+            Self::Plain => None,
+            Self::Html => Some("HTML"),
+            Self::MarkdownV2 => Some("MarkdownV2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Telegram {
+    pub url: String,
+    pub token: Secret,
+    pub chat_id: String,
+    pub thread_id: Option<i64>,
+    pub parse_mode: ParseMode,
+}
+
+/// JSON body for the sendMessage method
+#[derive(Debug, Serialize)]
+struct TelegramBody<'a> {
+    chat_id: &'a str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    message_thread_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<&'static str>,
+}
+
+/// Every Bot api response contains `ok`, with a `description` when it's false
+#[derive(Debug, Deserialize)]
+struct TelegramResponse {
+    ok: bool,
+    error_code: Option<i64>,
+    description: Option<String>,
+}
+
+impl Telegram {
+    /// Escape the characters that have a meaning in telegram HTML
+    fn escape_html(input: &str) -> String {
+        input
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    }
+
+    /// Escape every character that MarkdownV2 reserves
+    fn escape_markdown(input: &str) -> String {
+        let mut output = String::with_capacity(input.len());
+        for c in input.chars() {
+            if "_*[]()~`>#+-=|{}.!\\".contains(c) {
+                output.push('\\');
+            }
+            output.push(c);
+        }
+        output
+    }
+
+    fn gen_text(&self, message: &Message) -> String {
+        let ip = |ip: Option<IpAddr>| ip.map_or_else(|| S!("-"), |i| i.to_string());
+        let ips = [C!(message.local_ip), ip(message.ipv4), ip(message.ipv6)];
+        match self.parse_mode {
+            ParseMode::Plain => C!(message.text),
+            ParseMode::Html => format!(
+                "<b>{}</b> {}\n{}\n<i>{}</i>\n{}",
+                Self::escape_html(&message.machine_name),
+                Self::escape_html(&message.event),
+                Self::escape_html(&message.text),
+                Self::escape_html(&message.timestamp),
+                ips.map(|i| format!("<code>{}</code>", Self::escape_html(&i)))
+                    .join(" ")
+            ),
+            ParseMode::MarkdownV2 => format!(
+                "*{}* {}\n{}\n_{}_\n{}",
+                Self::escape_markdown(&message.machine_name),
+                Self::escape_markdown(&message.event),
+                Self::escape_markdown(&message.text),
+                Self::escape_markdown(&message.timestamp),
+                ips.map(|i| format!("`{}`", i.replace('\\', "\\\\").replace('`', "\\`")))
+                    .join(" ")
+            ),
+        }
+    }
+
+    fn gen_body(&self, message: &Message) -> TelegramBody<'_> {
+        TelegramBody {
+            chat_id: &self.chat_id,
+            text: self.gen_text(message),
+            message_thread_id: self.thread_id,
+            parse_mode: self.parse_mode.get(),
+        }
+    }
+}
+
+impl Notifier for Telegram {
+    fn name(&self) -> &'static str {
+        "telegram"
+    }
+
+    /// Telegram returns a json body for both success and failure, so check `ok` rather than the http status
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        let response = client
+            .post(format!(
+                "{}/bot{}/sendMessage",
+                self.url.trim_end_matches('/'),
+                self.token.expose()
+            ))
+            .json(&self.gen_body(message))
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        let status = response.status();
+        let body = response
+            .json::<TelegramResponse>()
+            .await
+            .map_err(reqwest::Error::without_url)?;
+        if body.ok {
+            Ok(Sent::default())
+        } else {
+            Err(AppError::Telegram(
+                body.error_code
+                    .unwrap_or_else(|| i64::from(status.as_u16())),
+                body.description.unwrap_or_default(),
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::tests::{StandIn, gen_message, gen_telegram};
+
+    #[test]
+    fn test_telegram_escape() {
+        assert_eq!(
+            Telegram::escape_html("<b>a & b</b>"),
+            "&lt;b&gt;a &amp; b&lt;/b&gt;"
+        );
+        assert_eq!(
+            Telegram::escape_markdown("2025-01-01 12:00:00 Europe/London"),
+            "2025\\-01\\-01 12:00:00 Europe/London"
+        );
+        assert_eq!(Telegram::escape_markdown("a_b.c!"), "a\\_b\\.c\\!");
+    }
+
+    #[test]
+    fn test_telegram_gen_text() {
+        let mut message = gen_message();
+        message.text = S!("test_machine ip changed, <old> & 1.1.1.1 -> 2.2.2.2");
+
+        let result = gen_telegram("", ParseMode::Plain).gen_text(&message);
+        assert_eq!(result, message.text);
+
+        let result = gen_telegram("", ParseMode::Html).gen_text(&message);
+        assert_eq!(
+            result,
+            "<b>test_machine</b> online\ntest_machine ip changed, &lt;old&gt; &amp; 1.1.1.1 -&gt; 2.2.2.2\n<i>2025-01-01 12:00:00 Europe/London</i>\n<code>192.168.0.10</code> <code>127.0.0.1</code> <code>::1</code>"
+        );
+
+        let result = gen_telegram("", ParseMode::MarkdownV2).gen_text(&message);
+        assert_eq!(
+            result,
+            "*test\\_machine* online\ntest\\_machine ip changed, <old\\> & 1\\.1\\.1\\.1 \\-\\> 2\\.2\\.2\\.2\n_2025\\-01\\-01 12:00:00 Europe/London_\n`192.168.0.10` `127.0.0.1` `::1`"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_telegram_send() {
+        let stand_in = StandIn::start(200, r#"{"ok":true,"result":{"message_id":1}}"#).await;
+        let mut telegram = gen_telegram(&stand_in.url, ParseMode::Html);
+        telegram.thread_id = Some(42);
+
+        let result = telegram.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].path, "/bot123:test_token/sendMessage");
+        let body = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(body["chat_id"], "-100123");
+        assert_eq!(body["message_thread_id"], 42);
+        assert_eq!(body["parse_mode"], "HTML");
+        assert!(
+            body["text"]
+                .as_str()
+                .unwrap()
+                .starts_with("<b>test_machine</b>")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_telegram_send_plain() {
+        let stand_in = StandIn::start(200, r#"{"ok":true,"result":{"message_id":1}}"#).await;
+        let telegram = gen_telegram(&stand_in.url, ParseMode::Plain);
+
+        let result = telegram.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_ok());
+        let body = serde_json::from_str::<Value>(&stand_in.requests()[0].body).unwrap();
+        assert!(body.get("parse_mode").is_none());
+        assert!(body.get("message_thread_id").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_telegram_send_err() {
+        let stand_in = StandIn::start(
+            400,
+            r#"{"ok":false,"error_code":400,"description":"Bad Request: chat not found"}"#,
+        )
+        .await;
+        let telegram = gen_telegram(&stand_in.url, ParseMode::Plain);
+
+        let result = telegram.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+        let result = result.unwrap_err();
+        assert!(matches!(result, AppError::Telegram(400, _)));
+        assert_eq!(
+            result.to_string(),
+            "Telegram error 400: Bad Request: chat not found"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use reqwest::{Client, Method};
+
+use crate::{C, S, app_error::AppError, secret::Secret};
+
+use super::{Message, Notifier, Sent};
+
+/// Body sent when `WEBHOOK_BODY` isn't set, every placeholder as a JSON object
+pub const WEBHOOK_BODY: &str = r#"{"machine_name":"{machine_name}","event":"{event}","local_ip":"{local_ip}","ipv4":"{ipv4}","ipv6":"{ipv6}","interfaces":"{interfaces}","timestamp":"{timestamp}","timezone":"{timezone}","message":"{message}"}"#;
+
+#[derive(Debug, Clone)]
+pub struct Webhook {
+    pub url: Secret,
+    pub method: Method,
+    /// Header values often contain credentials, so are all treated as secret
+    pub headers: Vec<(String, Secret)>,
+    /// Body template, see `Message::placeholders()` for the values that get substituted
+    pub body: String,
+}
+
+impl Webhook {
+    /// Escape a value so that it can be placed inside a JSON string
+    fn escape_json(value: &str) -> String {
+        let quoted = serde_json::Value::String(S!(value)).to_string();
+        S!(&quoted[1..quoted.len() - 1])
+    }
+
+    /// Is the body going to be sent as JSON, either by default or by a user set Content-Type header
+    fn is_json(&self) -> bool {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .is_none_or(|(_, value)| value.expose().to_lowercase().contains("json"))
+    }
+
+    /// Replace each placeholder in the template, JSON escaping the values when needed
+    fn gen_body(&self, message: &Message) -> String {
+        let is_json = self.is_json();
+        message
+            .placeholders()
+            .iter()
+            .fold(C!(self.body), |body, (placeholder, value)| {
+                if is_json {
+                    body.replace(placeholder, &Self::escape_json(value))
+                } else {
+                    body.replace(placeholder, value)
+                }
+            })
+    }
+}
+
+impl Notifier for Webhook {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    async fn send(&self, client: &Client, message: &Message) -> Result<Sent, AppError> {
+        let mut request = client.request(self.method.clone(), self.url.expose());
+        if self.is_json() {
+            request = request.header("Content-Type", "application/json");
+        }
+        for (name, value) in &self.headers {
+            request = request.header(name, value.expose());
+        }
+        if self.method != Method::GET {
+            request = request.body(self.gen_body(message));
+        }
+        request
+            .send()
+            .await
+            .map_err(reqwest::Error::without_url)?
+            .error_for_status()
+            .map_err(reqwest::Error::without_url)?;
+        Ok(Sent::default())
+    }
+}
+
+#[cfg(test)]
+#[expect(clippy::unwrap_used)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+    use crate::tests::{StandIn, gen_message, gen_webhook};
+
+    #[test]
+    fn test_webhook_gen_body() {
+        let mut message = gen_message();
+        message.machine_name = S!(r#"test "machine""#);
+        message.ipv6 = None;
+
+        let result = gen_webhook("").gen_body(&message);
+        let result = serde_json::from_str::<Value>(&result).unwrap();
+
+        assert_eq!(result["machine_name"], r#"test "machine""#);
+        assert_eq!(result["event"], "online");
+        assert_eq!(result["local_ip"], "192.168.0.10");
+        assert_eq!(result["ipv4"], "127.0.0.1");
+        assert_eq!(result["ipv6"], "");
+        assert_eq!(result["interfaces"], "eth0 192.168.0.10");
+        assert_eq!(result["timestamp"], "2025-01-01 12:00:00 Europe/London");
+        assert_eq!(result["timezone"], "Europe/London");
+        assert_eq!(result["message"], message.text);
+
+        let mut webhook = gen_webhook("");
+        webhook.headers = vec![(S!("content-type"), Secret::from("text/plain"))];
+        webhook.body = S!(r#"{machine_name} is {event}, {interfaces}"#);
+
+        let result = webhook.gen_body(&message);
+        assert_eq!(result, r#"test "machine" is online, eth0 192.168.0.10"#);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_send() {
+        let stand_in = StandIn::start(202, "").await;
+        let mut webhook = gen_webhook(&format!("{}/hook", stand_in.url));
+        webhook.method = Method::PUT;
+        webhook.headers = vec![(S!("Authorization"), Secret::from("Bearer secret"))];
+        let message = gen_message();
+
+        let result = webhook.send(&Client::new(), &message).await;
+
+        assert!(result.is_ok());
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].method, "PUT");
+        assert_eq!(requests[0].path, "/hook");
+        assert_eq!(requests[0].headers["authorization"], "Bearer secret");
+        assert_eq!(requests[0].headers["content-type"], "application/json");
+        let body = serde_json::from_str::<Value>(&requests[0].body).unwrap();
+        assert_eq!(body["message"], C!(message.text));
+    }
+
+    #[tokio::test]
+    async fn test_webhook_send_err() {
+        let stand_in = StandIn::start(500, "").await;
+        let webhook = gen_webhook(&format!("{}/hook", stand_in.url));
+
+        let result = webhook.send(&Client::new(), &gen_message()).await;
+
+        assert!(result.is_err());
+    }
+}
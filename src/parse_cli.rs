@@ -10,6 +10,14 @@ pub struct CliArgs {
     /// Uninstall the systemd service, need to run as sudo
     #[clap(short = 'u', long = "uninstall", conflicts_with = "install")]
     pub uninstall: bool,
+
+    /// Send a going offline message, used as the systemd ExecStop
+    #[clap(short = 's', long = "shutdown", conflicts_with_all = ["install", "uninstall"])]
+    pub shutdown: bool,
+
+    /// Keep running after the online message, sending a periodic heartbeat
+    #[clap(short = 'd', long = "daemon", conflicts_with_all = ["install", "uninstall", "shutdown"])]
+    pub daemon: bool,
 }
 
 impl CliArgs {
@@ -1,31 +1,31 @@
 use std::{fmt, net::IpAddr};
 
-use jiff::Zoned;
+use jiff::{Span, Unit, Zoned};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use sqlx::SqlitePool;
-use url::Url;
 
 use crate::{
-    C, S, app_env::AppEnv, app_error::AppError, db::ModelRequest, service_install::Status,
+    C, S,
+    app_env::AppEnv,
+    app_error::AppError,
+    db::{
+        ModelBoot, ModelBootLoop, ModelDelivery, ModelInterface, ModelOutbox, ModelPublicIp,
+        ModelReceipt, ModelRequest, ModelShutdown, ModelSkipRequest,
+    },
+    interface::Interface,
+    ip_provider::IpProvider,
+    notifier::{Backend, Delivery, Message, Pushover, send_all, send_fallback},
+    service_install::Status,
 };
 
-/// Pushover api url
-const URL: &str = "https://api.pushover.net/1/messages.json";
+#[cfg(target_os = "linux")]
+use crate::service_install::LinuxService;
 
-/// What's my ipv4 url
-const URL_V4: &str = "https://myipv4.p1.opendns.com/get_my_ip";
-/// What's my ipv6 url
-const URL_V6: &str = "https://myipv6.p1.opendns.com/get_my_ip";
-
-type Params<'a> = [(&'a str, String); 4];
-
-#[derive(Debug, Serialize, Deserialize)]
-/// Response from pushover api, currently not actually doing anything with it
-struct PostRequest {
-    status: usize,
-    request: String,
-}
+/// Seconds between each check of the pushover receipts api
+const RECEIPT_POLL: u64 = 30;
+/// Pushover stops repeating an emergency message after at most 3 hours, so a receipt older than this can't still be pending
+const RECEIPT_MAX_AGE: u64 = 60 * 60 * 3;
 
 /// Response from the what's my ip api
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -49,12 +49,55 @@ impl fmt::Display for Ip {
     }
 }
 
-#[allow(unused)]
 impl Ip {
-    const fn get_url(&self) -> &'static str {
+    fn get_providers<'a>(&self, app_env: &'a AppEnv) -> &'a [IpProvider] {
+        match self {
+            Self::V4 => &app_env.ip_providers.v4,
+            Self::V6 => &app_env.ip_providers.v6,
+        }
+    }
+
+    /// Some providers answer with whichever version the request was made over
+    const fn matches(&self, ip: IpAddr) -> bool {
+        match self {
+            Self::V4 => ip.is_ipv4(),
+            Self::V6 => ip.is_ipv6(),
+        }
+    }
+}
+
+/// A public ip, before & after a change, None where unknown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpChange {
+    pub old: Option<IpAddr>,
+    pub new: Option<IpAddr>,
+}
+
+impl IpChange {
+    /// "old -> new", or None if unchanged
+    fn describe(&self) -> Option<String> {
+        let ip = |i: Option<IpAddr>| i.map_or_else(|| S!("unknown"), |i| i.to_string());
+        (self.old != self.new).then(|| format!("{} -> {}", ip(self.old), ip(self.new)))
+    }
+}
+
+/// How the previous boot ended, worked out from the shutdown marker, with the downtime
+#[derive(Debug, Clone)]
+enum LastShutdown {
+    /// The shutdown hook ran
+    Clean(Span),
+    /// Crashed, or lost power, at some point after it was last known to be up
+    Unclean(Span),
+}
+
+impl fmt::Display for LastShutdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Self::V4 => URL_V4,
-            Self::V6 => URL_V6,
+            Self::Clean(downtime) => write!(f, "last shutdown clean, down for {downtime:#}"),
+            Self::Unclean(downtime) => write!(
+                f,
+                "last shutdown unclean, crashed or lost power, down for up to {downtime:#}"
+            ),
         }
     }
 }
@@ -62,6 +105,24 @@ impl Ip {
 pub enum PushRequest {
     Service(Status),
     Online,
+    /// Summary of how many alerts the rate limit dropped, sent along with the next message that's allowed, or by the daemon once a full window has passed since the last one was dropped
+    Suppressed(usize),
+    /// Escalated alert, sent once, in place of an online request, with the number of boots in the window
+    BootLoop(usize),
+    /// Sent periodically while running as a daemon
+    Heartbeat,
+    /// The machine is going offline, uptime in seconds, and the reason if it's known
+    Shutdown {
+        uptime: Option<u64>,
+        reason: Option<&'static str>,
+    },
+    /// Either public ip has changed, checked periodically while running as a daemon
+    IpChanged {
+        ipv4: IpChange,
+        ipv6: IpChange,
+    },
+    /// Each network interface that has appeared, disappeared, or changed, since the last snapshot
+    InterfaceChanged(Vec<String>),
 }
 
 impl From<Status> for PushRequest {
@@ -85,19 +146,30 @@ impl PushRequest {
             .build()?)
     }
 
-    #[cfg(not(test))]
-    /// Recursive function to check if network is up
-    async fn get_ip(mut count: u8, ip: Ip) -> Result<Option<IpResponse>, AppError> {
+    /// Recursive function to check if network is up, each provider is tried in order on every attempt
+    /// Returns straight away if there are no providers, as the lookup is disabled
+    async fn get_ip(
+        app_env: &AppEnv,
+        mut count: u8,
+        ip: Ip,
+    ) -> Result<Option<IpResponse>, AppError> {
+        let providers = ip.get_providers(app_env);
+        if providers.is_empty() {
+            return Ok(None);
+        }
+        let client = Self::get_client()?;
         loop {
             if count > 10 {
                 tracing::debug!("{ip} no response");
                 return Ok(None);
             }
-            if let Ok(client) = Self::get_client()
-                && let Ok(response) = client.get(ip.get_url()).send().await
-                && let Ok(resp) = response.json::<IpResponse>().await
-            {
-                return Ok(Some(resp));
+            for provider in providers {
+                if let Some(address) = provider.get(&client).await
+                    && ip.matches(address)
+                {
+                    return Ok(Some(IpResponse { ip: address }));
+                }
+                tracing::debug!("{ip} no response from {}", provider.url);
             }
             tracing::debug!("Recursively sleeping for 500ms");
             tokio::time::sleep(std::time::Duration::from_millis(500)).await;
@@ -105,46 +177,6 @@ impl PushRequest {
         }
     }
 
-    #[cfg(test)]
-    #[expect(clippy::unused_async)]
-    /// Test mock for ip, ipv6 issues on wsl :(
-    async fn get_ip(mut count: u8, ip: Ip) -> Result<Option<IpResponse>, AppError> {
-        use std::net::{Ipv4Addr, Ipv6Addr};
-        loop {
-            if count > 10 {
-                tracing::debug!("{ip} no response");
-                return Ok(None);
-            }
-
-            if count > 4 {
-                return Ok(Some(IpResponse {
-                    ip: match ip {
-                        Ip::V4 => IpAddr::V4(Ipv4Addr::LOCALHOST),
-                        Ip::V6 => IpAddr::V6(Ipv6Addr::LOCALHOST),
-                    },
-                }));
-            }
-            count += 1;
-        }
-    }
-
-    #[cfg(not(test))]
-    /// The actual request via PushOver api
-    async fn send_request(url: Url) -> Result<PostRequest, AppError> {
-        let client = Self::get_client()?;
-        Ok(client.post(url).send().await?.json::<PostRequest>().await?)
-    }
-
-    #[cfg(test)]
-    #[expect(clippy::unused_async)]
-    async fn send_request(_: Url) -> Result<PostRequest, AppError> {
-        let _client = Self::get_client()?;
-        Ok(PostRequest {
-            status: 1,
-            request: S!("request"),
-        })
-    }
-
     /// Basically fmt::Display for the current time using the app_env timezone
     fn format_offset(app_env: &AppEnv, offset: &Zoned) -> String {
         format!(
@@ -157,67 +189,672 @@ impl PushRequest {
         )
     }
 
-    /// Generate the params, aka the message
-    fn gen_params<'a>(
+    /// The name of the event, as used in the message
+    const fn event(&self) -> &'static str {
+        match self {
+            Self::Online => "online",
+            Self::Service(status) => status.get(),
+            Self::Suppressed(_) => "suppressed",
+            Self::BootLoop(_) => "boot loop",
+            Self::Heartbeat => "heartbeat",
+            Self::Shutdown { .. } => "offline",
+            Self::IpChanged { .. } => "ip changed",
+            Self::InterfaceChanged(_) => "interface changed",
+        }
+    }
+
+    /// Whether a previous request was made at boot
+    fn is_boot(request: &ModelRequest) -> bool {
+        matches!(
+            request.event.as_deref().unwrap_or("online"),
+            "online" | "boot loop"
+        )
+    }
+
+    /// Whether a previous request counts against the same rate limit allowance as this one
+    /// Requests made before the event was stored were all online events
+    fn shares_allowance(&self, request: &ModelRequest) -> bool {
+        let event = request.event.as_deref().unwrap_or("online");
+        match self {
+            Self::Online => event == "online",
+            Self::Shutdown { .. } => event == "offline",
+            Self::IpChanged { .. } => event == "ip changed",
+            Self::InterfaceChanged(_) => event == "interface changed",
+            Self::Service(_) => event.starts_with("service "),
+            Self::Suppressed(_) | Self::BootLoop(_) | Self::Heartbeat => false,
+        }
+    }
+
+    /// The number of messages allowed in each window, None if never rate limited
+    const fn allowance(&self, app_env: &AppEnv) -> Option<usize> {
+        match self {
+            Self::Online
+            | Self::Shutdown { .. }
+            | Self::IpChanged { .. }
+            | Self::InterfaceChanged(_) => Some(app_env.rate_limit.max),
+            Self::Service(_) => Some(app_env.rate_limit.max_service),
+            Self::Suppressed(_) | Self::BootLoop(_) | Self::Heartbeat => None,
+        }
+    }
+
+    /// Generate the message, which each notifier will then send in its own format
+    fn gen_message(
         &self,
         app_env: &AppEnv,
         ipv4: Option<IpResponse>,
         ipv6: Option<IpResponse>,
-    ) -> Params<'a> {
-        let mut params = [
-            ("token", C!(app_env.token_app)),
-            ("user", C!(app_env.token_user)),
-            ("message", S!()),
-            ("priority", S!("0")),
-        ];
-
+        last_shutdown: Option<&LastShutdown>,
+    ) -> Message {
+        let interfaces = Interface::get_all(&app_env.interface_filter);
         let local_ip =
-            local_ip_address::local_ip().map_or_else(|_| S!("UNKNOWN"), |ip| ip.to_string());
+            Interface::local_ip(&interfaces).map_or_else(|| S!("UNKNOWN"), |ip| ip.to_string());
+        let interfaces = Interface::fmt_all(&interfaces);
+        let timestamp = Self::format_offset(app_env, &ModelRequest::now_with_offset(app_env));
+        let ipv4 = ipv4.map(|i| i.ip);
+        let ipv6 = ipv6.map(|i| i.ip);
 
         let suffix = format!(
-            "@ {} {} {} {}",
-            Self::format_offset(app_env, &ModelRequest::now_with_offset(app_env)),
+            "@ {timestamp} {local_ip} {} {}",
+            ipv4.map_or(String::new(), |i| i.to_string()),
+            ipv6.map_or(String::new(), |i| i.to_string())
+        );
+
+        let text = match self {
+            Self::Online => format!(
+                "{} online{} {}{}",
+                app_env.machine_name,
+                last_shutdown.map_or_else(String::new, |i| format!(", {i}")),
+                suffix.trim_end(),
+                if interfaces.is_empty() {
+                    String::new()
+                } else {
+                    format!(", interfaces {interfaces}")
+                }
+            ),
+            Self::Service(status) => {
+                format!("{} on {} {suffix}", status.get(), app_env.machine_name)
+            }
+            Self::Suppressed(count) => format!(
+                "{} had {count} alert{} suppressed by the rate limit {suffix}",
+                app_env.machine_name,
+                if *count == 1 { "" } else { "s" }
+            ),
+            Self::BootLoop(boots) => format!(
+                "{} is boot looping, {boots} boots in the past {} seconds {suffix}",
+                app_env.machine_name, app_env.boot_loop.window
+            ),
+            Self::Heartbeat => format!(
+                "{} still up, uptime {:#} {suffix}",
+                app_env.machine_name,
+                Self::duration_between(app_env, Self::booted_at(), ModelRequest::now())
+            ),
+            Self::Shutdown { uptime, reason } => format!(
+                "{} going offline{}{} {suffix}",
+                app_env.machine_name,
+                reason.map_or_else(String::new, |i| format!(", {i}")),
+                uptime.map_or_else(String::new, |i| format!(
+                    ", up {:#}",
+                    jiff::SignedDuration::from_secs(i64::try_from(i).unwrap_or(i64::MAX))
+                ))
+            ),
+            Self::IpChanged { ipv4, ipv6 } => format!(
+                "{} public ip changed, {} {suffix}",
+                app_env.machine_name,
+                [("ipv4", ipv4), ("ipv6", ipv6)]
+                    .iter()
+                    .filter_map(|(name, change)| change.describe().map(|i| format!("{name} {i}")))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Self::InterfaceChanged(changes) => format!(
+                "{} interfaces changed, {} {suffix}",
+                app_env.machine_name,
+                changes.join(", ")
+            ),
+        };
+
+        Message {
+            machine_name: C!(app_env.machine_name),
+            event: S!(self.event()),
+            timestamp,
+            unix_timestamp: ModelRequest::now(),
+            timezone: S!(app_env.timezone.iana_name().unwrap_or_default()),
             local_ip,
-            ipv4.map_or(String::new(), |i| i.ip.to_string()),
-            ipv6.map_or(String::new(), |i| i.ip.to_string())
+            ipv4,
+            ipv6,
+            interfaces,
+            text,
+            escalated: matches!(self, Self::BootLoop(_)),
+        }
+    }
+
+    /// The time between two timestamps, using the calendar of the configured timezone
+    fn duration_between(app_env: &AppEnv, from: u64, to: u64) -> Span {
+        let zoned = |i: u64| {
+            i64::try_from(i)
+                .ok()
+                .and_then(|i| jiff::Timestamp::from_second(i).ok())
+                .unwrap_or_default()
+                .to_zoned(C!(app_env.timezone))
+        };
+        zoned(from)
+            .until((Unit::Day, &zoned(to.max(from))))
+            .unwrap_or_default()
+    }
+
+    /// When this boot happened, seconds since the unix epoch
+    fn booted_at() -> u64 {
+        #[cfg(target_os = "linux")]
+        let uptime = LinuxService::get_uptime().unwrap_or_default();
+        #[cfg(not(target_os = "linux"))]
+        let uptime = 0;
+        ModelRequest::now().saturating_sub(uptime)
+    }
+
+    /// Compare the previous boot with the shutdown marker, None if this is the first boot
+    /// Must be checked before this boot's request is inserted
+    async fn get_last_shutdown(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        booted_at: u64,
+    ) -> Result<Option<LastShutdown>, AppError> {
+        let Some(last_boot) = ModelRequest::get_last_boot(db).await? else {
+            return Ok(None);
+        };
+        match ModelShutdown::get_last(db).await? {
+            Some(shutdown) if shutdown.timestamp > last_boot.timestamp => {
+                Ok(Some(LastShutdown::Clean(Self::duration_between(
+                    app_env,
+                    shutdown.timestamp,
+                    booted_at,
+                ))))
+            }
+            _ => {
+                let last_seen = ModelRequest::get_last(db)
+                    .await?
+                    .map_or(last_boot.timestamp, |i| i.timestamp);
+                Ok(Some(LastShutdown::Unclean(Self::duration_between(
+                    app_env, last_seen, booted_at,
+                ))))
+            }
+        }
+    }
+
+    /// Log the outcome of each delivery attempt, and store it against the request
+    async fn record(
+        db: &SqlitePool,
+        request_id: i64,
+        deliveries: &[Delivery],
+        fallback: bool,
+    ) -> Result<(), AppError> {
+        let kind = if fallback { "fallback" } else { "notifier" };
+        for delivery in deliveries {
+            match &delivery.result {
+                Ok(sent) => {
+                    tracing::info!("{kind} {} delivered", delivery.notifier);
+                    if let Some(id) = &sent.id
+                        && delivery.notifier == "pushover"
+                    {
+                        ModelRequest::set_pushover_request(db, request_id, id).await?;
+                    }
+                    if let Some(receipt) = &sent.receipt {
+                        ModelReceipt::insert(db, request_id, receipt).await?;
+                    }
+                }
+                Err(e) => tracing::error!("{kind} {} failed: {e}", delivery.notifier),
+            }
+            ModelDelivery::insert(
+                db,
+                request_id,
+                delivery.notifier,
+                fallback,
+                delivery.result.as_ref().err().map(ToString::to_string),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Send the message via every notifier, then the fallbacks if none of those delivered it
+    /// Returns true if at least one notifier, or fallback, delivered the message
+    async fn deliver(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        request_id: i64,
+        message: &Message,
+    ) -> Result<bool, AppError> {
+        let client = Self::get_client()?;
+        let deliveries = send_all(&app_env.notifiers, &client, message).await;
+        Self::record(db, request_id, &deliveries, false).await?;
+
+        let mut delivered = deliveries.iter().any(|i| i.result.is_ok());
+        if !delivered && !app_env.fallbacks.is_empty() {
+            tracing::debug!("Trying fallback notifiers");
+            let deliveries = send_fallback(&app_env.fallbacks, &client, message).await;
+            Self::record(db, request_id, &deliveries, true).await?;
+            delivered = delivered || deliveries.iter().any(|i| i.result.is_ok());
+        }
+        if delivered {
+            ModelRequest::set_delivered(db, request_id).await?;
+            ModelBoot::set_notified(db, request_id).await?;
+        }
+        Ok(delivered)
+    }
+
+    /// Retry every queued message that is due, each keeps its original text & timestamp
+    pub async fn retry_outbox(app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
+        for outbox in ModelOutbox::get_pending(db).await? {
+            tracing::debug!("Retrying {outbox}");
+            let delivered = match outbox.get_message() {
+                Ok(message) => Self::deliver(app_env, db, outbox.request_id, &message).await?,
+                Err(e) => {
+                    tracing::error!("outbox {} invalid message: {e}", outbox.outbox_id);
+                    false
+                }
+            };
+            if delivered {
+                outbox.set_delivered(db).await?;
+                tracing::info!("Queued message delivered, {outbox}");
+            } else {
+                let outbox = outbox.retry(db).await?;
+                tracing::info!("Queued message undelivered, {outbox}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a single receipt, a receipt pushover returns a 4xx for, or which is too old, won't ever be acknowledged
+    async fn check_receipt(
+        pushover: &Pushover,
+        client: &Client,
+        db: &SqlitePool,
+        receipt: ModelReceipt,
+    ) -> Result<(), AppError> {
+        if ModelRequest::now().saturating_sub(receipt.created) > RECEIPT_MAX_AGE {
+            let receipt = receipt.set_expired(db).await?;
+            tracing::info!("Receipt too old to check, {receipt}");
+            return Ok(());
+        }
+        match pushover.check_receipt(client, &receipt.receipt).await {
+            Ok(status) => {
+                let receipt = receipt.update(db, &status).await?;
+                if receipt.acknowledged {
+                    tracing::info!("Receipt acknowledged, {receipt}");
+                } else if receipt.expired {
+                    tracing::info!("Receipt expired, {receipt}");
+                }
+            }
+            Err(AppError::Pushover(status, e)) if (400..500).contains(&status) => {
+                let receipt = receipt.set_expired(db).await?;
+                tracing::error!("Receipt invalid {status}: {e}, {receipt}");
+            }
+            Err(e) => tracing::error!("Receipt {}: {e}", receipt.receipt),
+        }
+        Ok(())
+    }
+
+    /// The first pushover notifier, or fallback, which is used to check receipts
+    fn get_pushover(app_env: &AppEnv) -> Option<&Pushover> {
+        app_env
+            .notifiers
+            .iter()
+            .chain(&app_env.fallbacks)
+            .find_map(|i| match i {
+                Backend::Pushover(pushover) => Some(pushover),
+                _ => None,
+            })
+    }
+
+    /// Check each pending emergency priority receipt once
+    pub async fn check_receipts(app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
+        let Some(pushover) = Self::get_pushover(app_env) else {
+            return Ok(());
+        };
+        let client = Self::get_client()?;
+        for receipt in ModelReceipt::get_pending(db).await? {
+            Self::check_receipt(pushover, &client, db, receipt).await?;
+        }
+        Ok(())
+    }
+
+    /// Poll every emergency priority receipt until it's acknowledged, or it expires, recording who acknowledged it
+    pub async fn poll_receipts(app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
+        if Self::get_pushover(app_env).is_none() {
+            return Ok(());
+        }
+        loop {
+            Self::check_receipts(app_env, db).await?;
+            if ModelReceipt::get_pending(db).await?.is_empty() {
+                return Ok(());
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(RECEIPT_POLL)).await;
+        }
+    }
+
+    /// Insert the request, and send the message, queuing it in the outbox if it isn't delivered
+    /// Returns true if it was delivered
+    async fn send(
+        &self,
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        message: &Message,
+        boot_id: Option<&str>,
+    ) -> Result<bool, AppError> {
+        let request = ModelRequest::insert(db, self.event(), false).await?;
+        if let Some(boot_id) = boot_id {
+            ModelBoot::set_request(db, boot_id, request.request_id, message.ipv4, message.ipv6)
+                .await?;
+        }
+        let delivered = Self::deliver(app_env, db, request.request_id, message).await?;
+        if !delivered {
+            let outbox = ModelOutbox::insert(db, request.request_id, message).await?;
+            tracing::info!("Message queued for retry, {outbox}");
+        }
+        Ok(delivered)
+    }
+
+    /// Check if the rate limit allowance for this event has been used up in the current window
+    /// If so the request is stored as suppressed, to be reported in the next summary
+    async fn rate_limited(&self, app_env: &AppEnv, db: &SqlitePool) -> Result<bool, AppError> {
+        let Some(allowance) = self.allowance(app_env) else {
+            return Ok(false);
+        };
+        let requests_made = ModelRequest::get_window(db, app_env.rate_limit.window)
+            .await?
+            .into_iter()
+            .filter(|i| !i.suppressed && self.shares_allowance(i))
+            .collect::<Vec<_>>();
+        if requests_made.len() < allowance {
+            return Ok(false);
+        }
+        tracing::info!(
+            "{} {} requests made in past {} seconds, suppressing request",
+            requests_made.len(),
+            self.event(),
+            app_env.rate_limit.window
         );
+        for i in requests_made {
+            tracing::info!(
+                "{}",
+                Self::format_offset(app_env, &i.timestamp_to_offset(app_env))
+            );
+        }
+        ModelRequest::insert(db, self.event(), true).await?;
+        Ok(true)
+    }
 
-        match self {
-            Self::Online => {
-                params[2].1 = format!("{} online {suffix}", app_env.machine_name,);
+    /// Check the request history for a boot loop, once detected only a single escalated alert is sent, and the machine then stays quiet until no boot has happened for the stable period
+    /// Returns the request to make in place of an online request, None if it should be suppressed
+    async fn check_boot_loop(app_env: &AppEnv, db: &SqlitePool) -> Result<Option<Self>, AppError> {
+        let config = app_env.boot_loop;
+        if config.count == 0 {
+            return Ok(Some(Self::Online));
+        }
+        if let Some(boot_loop) = ModelBootLoop::get(db).await {
+            if ModelRequest::get_window(db, config.stable)
+                .await?
+                .iter()
+                .any(Self::is_boot)
+            {
+                tracing::info!("Boot loop ongoing, muting request, {boot_loop}");
+                return Ok(None);
             }
-            Self::Service(status) => {
-                params[2].1 = format!("{} on {} {suffix}", status.get(), app_env.machine_name,);
+            ModelBootLoop::delete(db).await?;
+            tracing::info!(
+                "No boot in past {} seconds, boot loop over, {boot_loop}",
+                config.stable
+            );
+        }
+        // Including this boot
+        let boots = ModelRequest::get_window(db, config.window)
+            .await?
+            .iter()
+            .filter(|i| Self::is_boot(i))
+            .count()
+            + 1;
+        if boots >= config.count {
+            let boot_loop = ModelBootLoop::insert(db, boots).await?;
+            tracing::info!("Boot loop detected, {boot_loop}");
+            return Ok(Some(Self::BootLoop(boots)));
+        }
+        Ok(Some(Self::Online))
+    }
+
+    /// Record the clean shutdown marker, then send the going offline message, with the uptime & reason where available
+    /// On Linux nothing happens without a shutdown reason, as ExecStop also runs when only the service is stopped or restarted
+    /// Muted while boot looping, the caller applies the shutdown deadline
+    pub async fn make_shutdown_request(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        reason: Option<&'static str>,
+    ) -> Result<(), AppError> {
+        #[cfg(target_os = "linux")]
+        if reason.is_none() {
+            tracing::info!("Only the service is stopping, no shutdown recorded");
+            return Ok(());
+        }
+        #[cfg(target_os = "linux")]
+        let uptime = LinuxService::get_uptime();
+        #[cfg(not(target_os = "linux"))]
+        let uptime = None;
+        let shutdown = ModelShutdown::insert(db, reason).await?;
+        tracing::debug!("Shutdown recorded, {shutdown}");
+        let request = Self::Shutdown { uptime, reason };
+        if ModelSkipRequest::get(db).await.is_none_or(|i| i.skip) {
+            return Ok(());
+        }
+        if let Some(boot_loop) = ModelBootLoop::get(db).await {
+            tracing::info!("Boot loop ongoing, muting request, {boot_loop}");
+            ModelRequest::insert_muted(db, request.event()).await?;
+            return Ok(());
+        }
+        request.request(app_env, db, None).await
+    }
+
+    /// Look up the public ips, and send an alert if either has changed since the last check
+    /// A failed lookup keeps the last known address, and the first check only records the addresses
+    pub async fn check_public_ip(app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
+        let (ipv4, ipv6) = tokio::try_join!(
+            Self::get_ip(app_env, 0, Ip::V4),
+            Self::get_ip(app_env, 0, Ip::V6)
+        )?;
+        let (ipv4, ipv6) = (ipv4.map(|i| i.ip), ipv6.map(|i| i.ip));
+        let Some(known) = ModelPublicIp::get(db).await? else {
+            let public_ip = ModelPublicIp::upsert(db, ipv4, ipv6).await?;
+            tracing::debug!("Public ip recorded, {public_ip}");
+            return Ok(());
+        };
+        let request = Self::IpChanged {
+            ipv4: IpChange {
+                old: known.ipv4(),
+                new: ipv4.or_else(|| known.ipv4()),
+            },
+            ipv6: IpChange {
+                old: known.ipv6(),
+                new: ipv6.or_else(|| known.ipv6()),
+            },
+        };
+        if let Self::IpChanged { ipv4, ipv6 } = &request {
+            if ipv4.old == ipv4.new && ipv6.old == ipv6.new {
+                return Ok(());
             }
+            let public_ip = ModelPublicIp::upsert(db, ipv4.new, ipv6.new).await?;
+            tracing::info!("Public ip changed, {public_ip}");
         }
-        params
+        request.make_request(app_env, db).await
     }
 
-    /// Make the request, will check to make sure that haven't made 6+ request in past hour
-    /// get_ip functions are recursive, to deal with no network at first boot
-    #[allow(clippy::cognitive_complexity)]
+    /// Compare the interfaces against the last snapshot, and send an alert if any have appeared, disappeared, or changed
+    /// The snapshot is always replaced, the first one is only recorded, and no alert is sent while requests are skipped
+    pub async fn check_interfaces(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        interfaces: Vec<Interface>,
+    ) -> Result<(), AppError> {
+        let previous = ModelInterface::get_all(db)
+            .await?
+            .into_iter()
+            .map(Interface::from)
+            .collect::<Vec<_>>();
+        ModelInterface::replace(db, &interfaces).await?;
+        if previous.is_empty() {
+            tracing::debug!("Interfaces recorded, {} found", interfaces.len());
+            return Ok(());
+        }
+        let changes = Interface::changes(&previous, &interfaces);
+        if changes.is_empty() {
+            return Ok(());
+        }
+        tracing::info!("Interfaces changed, {}", changes.join(", "));
+        if ModelSkipRequest::get(db).await.is_none_or(|i| i.skip) {
+            return Ok(());
+        }
+        Self::InterfaceChanged(changes)
+            .make_request(app_env, db)
+            .await
+    }
+
+    #[cfg(all(target_os = "linux", not(test)))]
+    /// The linux boot id
+    fn get_boot_id() -> Option<String> {
+        LinuxService::get_boot_id()
+    }
+
+    #[cfg(any(not(target_os = "linux"), test))]
+    /// Other platforms don't have a boot id, so every run is treated as a new boot, as is every online request in tests
+    const fn get_boot_id() -> Option<String> {
+        None
+    }
+
+    /// Record this boot, returns false if it's already been recorded, aka the service has been restarted
+    async fn record_boot(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        boot_id: &str,
+    ) -> Result<bool, AppError> {
+        #[cfg(target_os = "linux")]
+        let (kernel, hostname) = (LinuxService::get_kernel(), LinuxService::get_hostname());
+        #[cfg(not(target_os = "linux"))]
+        let (kernel, hostname): (Option<String>, Option<String>) = (None, None);
+        let local_ip = Interface::local_ip(&Interface::get_all(&app_env.interface_filter))
+            .map(|i| i.to_string());
+
+        match ModelBoot::insert(
+            db,
+            boot_id,
+            Self::booted_at(),
+            kernel.as_deref(),
+            hostname.as_deref(),
+            local_ip.as_deref(),
+        )
+        .await?
+        {
+            Some(boot) => {
+                tracing::debug!("Boot recorded, {boot}");
+                Ok(true)
+            }
+            None => {
+                tracing::info!("Boot {boot_id} already recorded, skipping request");
+                Ok(false)
+            }
+        }
+    }
+
+    /// Make the request, an online request is only ever made once per boot
     pub async fn make_request(&self, app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
-        let requests_made = ModelRequest::get_past_hour(db).await?;
-
-        if requests_made.len() >= 6 {
-            tracing::info!("6 Requests made in past hour, skipping sending request");
-            for i in requests_made {
-                tracing::info!(
-                    "{}",
-                    Self::format_offset(app_env, &i.timestamp_to_offset(app_env))
-                );
+        if matches!(self, Self::Online) {
+            Self::boot_request(app_env, db, Self::get_boot_id().as_deref()).await
+        } else {
+            self.request(app_env, db, None).await
+        }
+    }
+
+    /// The online request for a boot, replaced by an escalated alert if the machine is boot looping
+    async fn boot_request(
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        boot_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        if let Some(boot_id) = boot_id
+            && !Self::record_boot(app_env, db, boot_id).await?
+        {
+            return Ok(());
+        }
+        match Self::check_boot_loop(app_env, db).await? {
+            Some(request) => request.request(app_env, db, boot_id).await,
+            None => {
+                ModelRequest::insert_muted(db, Self::Online.event()).await?;
+                Ok(())
             }
+        }
+    }
+
+    /// Send the summary of suppressed alerts on its own, once a full window has passed since the last one was dropped
+    /// Used by the daemon, so the summary isn't left until the next message
+    pub async fn report_suppressed(app_env: &AppEnv, db: &SqlitePool) -> Result<(), AppError> {
+        let suppressed = ModelRequest::get_suppressed(db, Self::Suppressed(0).event()).await?;
+        if suppressed
+            .last()
+            .is_none_or(|i| i.timestamp + app_env.rate_limit.window > ModelRequest::now())
+        {
+            return Ok(());
+        }
+        Self::Suppressed(suppressed.len())
+            .request(app_env, db, None)
+            .await
+    }
+
+    /// Make the request, unless the rate limit allowance for the event has been used up
+    /// Any alerts suppressed since the last summary are reported first
+    /// get_ip functions are recursive, to deal with no network at first boot, they're skipped at shutdown, as the network is already up, and for an ip change, as they've just been looked up
+    #[allow(clippy::cognitive_complexity)]
+    async fn request(
+        &self,
+        app_env: &AppEnv,
+        db: &SqlitePool,
+        boot_id: Option<&str>,
+    ) -> Result<(), AppError> {
+        let last_shutdown = if matches!(self, Self::Online) {
+            Self::get_last_shutdown(app_env, db, Self::booted_at()).await?
         } else {
-            tracing::debug!("Checking network connection");
-            let (ipv4, ipv6) = tokio::try_join!(Self::get_ip(0, Ip::V4), Self::get_ip(0, Ip::V6))?;
+            None
+        };
+        if self.rate_limited(app_env, db).await? {
+            return Ok(());
+        }
+        let (ipv4, ipv6) = match self {
+            Self::Shutdown { .. } => (None, None),
+            // Already looked up when checking for the change
+            Self::IpChanged { ipv4, ipv6 } => (
+                ipv4.new.map(|ip| IpResponse { ip }),
+                ipv6.new.map(|ip| IpResponse { ip }),
+            ),
+            _ => {
+                tracing::debug!("Checking network connection");
+                tokio::try_join!(
+                    Self::get_ip(app_env, 0, Ip::V4),
+                    Self::get_ip(app_env, 0, Ip::V6)
+                )?
+            }
+        };
 
-            tracing::debug!("Sending request");
-            let params = self.gen_params(app_env, ipv4, ipv6);
-            let url = reqwest::Url::parse_with_params(URL, &params)?;
-            ModelRequest::insert(db).await?;
-            Self::send_request(url).await?;
-            tracing::debug!("Request sent");
+        let suppressed = ModelRequest::get_suppressed(db, Self::Suppressed(0).event()).await?;
+        if !suppressed.is_empty() && !matches!(self, Self::Suppressed(_)) {
+            tracing::debug!("Sending suppressed summary");
+            let summary = Self::Suppressed(suppressed.len());
+            let message = summary.gen_message(app_env, C!(ipv4), C!(ipv6), None);
+            // The outbox retries the summary, so it doesn't fail the request itself
+            if !summary.send(app_env, db, &message, None).await? {
+                tracing::warn!("Suppressed summary undelivered, left in the outbox");
+            }
         }
+
+        tracing::debug!("Sending request");
+        let message = self.gen_message(app_env, ipv4, ipv6, last_shutdown.as_ref());
+        if !self.send(app_env, db, &message, boot_id).await? {
+            return Err(AppError::Undelivered);
+        }
+        tracing::debug!("Request sent");
         Ok(())
     }
 }
@@ -229,7 +866,20 @@ mod tests {
     use std::net::{Ipv4Addr, Ipv6Addr};
 
     use super::*;
-    use crate::tests::{setup_test, test_cleanup};
+    use crate::{
+        ip_provider::IpProviders,
+        notifier::{Backend, Discord, Gotify, Pushover, Slack},
+        secret::Secret,
+        tests::{StandIn, gen_emergency, gen_pushover, gen_webhook, setup_test, test_cleanup},
+    };
+
+    /// Stand-in providers, answering with the localhost ipv4 & ipv6 addresses
+    async fn gen_ip_stand_ins() -> (StandIn, StandIn) {
+        (
+            StandIn::start(200, "127.0.0.1").await,
+            StandIn::start(200, "::1\n").await,
+        )
+    }
 
     #[tokio::test]
     async fn test_request_format_offset() {
@@ -285,7 +935,7 @@ mod tests {
         " Europe/London 172.17.0"
     }
     #[tokio::test]
-    async fn test_request_generate_params() {
+    async fn test_request_generate_message() {
         let (app_env, db, uuid) = setup_test().await;
         let ipv4 = IpResponse {
             ip: IpAddr::V4(Ipv4Addr::LOCALHOST),
@@ -295,52 +945,51 @@ mod tests {
         };
 
         let push_request = PushRequest::Online;
-        let result = push_request.gen_params(&app_env, Some(ipv4.clone()), Some(ipv6.clone()));
-
-        // This will fail when the utc/london timezones aren't in sync
-        assert_eq!(result[0], ("token", S!("test_token_app")));
+        let result =
+            push_request.gen_message(&app_env, Some(ipv4.clone()), Some(ipv6.clone()), None);
 
-        println!("{}", result[2].1);
+        println!("{}", result.text);
 
-        assert_eq!(result[2].0, "message");
-        assert!(result[2].1.starts_with("test_machine online @ 20"));
+        assert!(result.text.starts_with("test_machine online @ 20"));
+        assert!(
+            result.interfaces.is_empty()
+                || result
+                    .text
+                    .ends_with(&format!(", interfaces {}", result.interfaces))
+        );
         // 192.168.0.19
-        assert!(result[2].1.contains(test_ip()));
-        assert!(result[2].1.contains(" 127.0.0.1 ::1"));
-
-        assert_eq!(result[1], ("user", S!("test_token_user")));
-
-        assert_eq!(result[3], ("priority", S!("0")));
+        assert!(result.text.contains(test_ip()));
+        assert!(result.text.contains(" 127.0.0.1 ::1"));
+        assert_eq!(result.machine_name, "test_machine");
+        assert_eq!(result.event, "online");
+        assert_eq!(result.timezone, "Europe/London");
+        assert_eq!(result.ipv4, Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(result.ipv6, Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
 
         let push_request = PushRequest::Service(Status::Install);
-        let result = push_request.gen_params(&app_env, Some(ipv4.clone()), Some(ipv6.clone()));
+        let result =
+            push_request.gen_message(&app_env, Some(ipv4.clone()), Some(ipv6.clone()), None);
 
-        assert_eq!(result[0], ("token", S!("test_token_app")));
-        assert_eq!(result[2].0, "message");
         assert!(
-            result[2]
-                .1
+            result
+                .text
                 .starts_with("service installed on test_machine @ 20")
         );
-        assert!(result[2].1.contains(test_ip()));
-        assert!(result[2].1.contains(" 127.0.0.1 ::1"));
-        assert_eq!(result[1], ("user", S!("test_token_user")));
-        assert_eq!(result[3], ("priority", S!("0")));
+        assert!(result.text.contains(test_ip()));
+        assert!(result.text.contains(" 127.0.0.1 ::1"));
+        assert_eq!(result.event, "service installed");
 
         let push_request = PushRequest::Service(Status::Uninstall);
-        let result = push_request.gen_params(&app_env, Some(ipv4), Some(ipv6));
+        let result = push_request.gen_message(&app_env, Some(ipv4), Some(ipv6), None);
 
-        assert_eq!(result[0], ("token", S!("test_token_app")));
-        assert_eq!(result[2].0, "message");
         assert!(
-            result[2]
-                .1
+            result
+                .text
                 .starts_with("service uninstalled on test_machine @ 20")
         );
-        assert!(result[2].1.contains(test_ip()));
-        assert!(result[2].1.contains(" 127.0.0.1 ::1"));
-        assert_eq!(result[1], ("user", S!("test_token_user")));
-        assert_eq!(result[3], ("priority", S!("0")));
+        assert!(result.text.contains(test_ip()));
+        assert!(result.text.contains(" 127.0.0.1 ::1"));
+        assert_eq!(result.event, "service uninstalled");
 
         test_cleanup(uuid, Some(db)).await;
     }
@@ -348,11 +997,12 @@ mod tests {
     #[tokio::test]
     // Request not made if 6+ requests been made in previous 60 minutes
     async fn test_request_make_request_not_made() {
-        let (app_env, db, uuid) = setup_test().await;
+        let (mut app_env, db, uuid) = setup_test().await;
+        app_env.boot_loop.count = 0;
 
         let now = i64::try_from(ModelRequest::now()).unwrap();
         for i in 1..=6 {
-            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING request_id, timestamp";
+            let sql = "INSERT INTO request(timestamp) VALUES ($1) RETURNING *";
             let timestamp = now - (60 * (i * 2));
 
             sqlx::query_as::<_, ModelRequest>(sql)
@@ -370,19 +1020,642 @@ mod tests {
 
         assert!(result.is_ok());
 
+        // Only stored as suppressed
         let request_len = ModelRequest::get_all(&db).await;
         assert!(request_len.is_ok());
-        assert_eq!(request_len.unwrap().len(), 6);
+        let request_len = request_len.unwrap();
+        assert_eq!(request_len.len(), 7);
+        assert!(request_len[6].suppressed);
+        assert!(!request_len[6].delivered);
 
         test_cleanup(uuid, Some(db)).await;
     }
 
     #[tokio::test]
-    // Request made, and inserted into db
-    async fn test_request_get_ip_count() {
-        let result = PushRequest::get_ip(11, Ip::V4).await;
+    // The summary is only sent on its own once a full window has passed since the last alert was suppressed, and only once
+    async fn test_request_report_suppressed() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+
+        PushRequest::report_suppressed(&app_env, &db).await.unwrap();
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+        PushRequest::report_suppressed(&app_env, &db).await.unwrap();
+        assert!(ok.requests().is_empty());
+
+        sqlx::query("UPDATE request SET timestamp = timestamp - $1")
+            .bind(i64::try_from(app_env.rate_limit.window).unwrap())
+            .execute(&db)
+            .await
+            .unwrap();
+        PushRequest::report_suppressed(&app_env, &db).await.unwrap();
+        PushRequest::report_suppressed(&app_env, &db).await.unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body.contains(r#""event":"suppressed""#));
+        assert!(
+            sent[0]
+                .body
+                .contains("test_machine had 2 alerts suppressed by the rate limit @ ")
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // An undelivered summary is left to the outbox, and doesn't fail the delivered request
+    async fn test_request_make_request_summary_undelivered() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start_after_failures(1, 200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.boot_loop.count = 0;
+        ModelRequest::insert(&db, "online", true).await.unwrap();
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
         assert!(result.is_ok());
-        assert!(result.unwrap().is_none())
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 2);
+        assert!(sent[0].body.contains(r#""event":"suppressed""#));
+        assert!(sent[1].body.contains(r#""event":"online""#));
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(requests.len(), 3);
+        assert!(!requests[1].delivered);
+        assert!(requests[2].delivered);
+        assert_eq!(ModelOutbox::get_all(&db).await.unwrap().len(), 1);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Once the allowance is used up requests are suppressed, and reported along with the next message that's allowed
+    async fn test_request_make_request_suppressed() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.rate_limit.max = 2;
+        app_env.rate_limit.max_service = 1;
+        app_env.boot_loop.count = 0;
+
+        // Service events have their own allowance
+        PushRequest::Service(Status::Install)
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        PushRequest::Service(Status::Uninstall)
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        assert_eq!(ok.requests().len(), 1);
+
+        // The suppressed uninstall is reported along with the first online message
+        for _ in 0..3 {
+            PushRequest::Online
+                .make_request(&app_env, &db)
+                .await
+                .unwrap();
+        }
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 4);
+        assert!(sent[1].body.contains(r#""event":"suppressed""#));
+        assert!(
+            sent[1]
+                .body
+                .contains("test_machine had 1 alert suppressed by the rate limit @ ")
+        );
+
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(requests.len(), 6);
+        assert!(requests[1].suppressed);
+        assert_eq!(requests[1].event.as_deref(), Some("service uninstalled"));
+        assert!(!requests[1].delivered);
+        assert_eq!(requests[2].event.as_deref(), Some("suppressed"));
+        assert!(requests[2].delivered);
+        assert!(requests[5].suppressed);
+        assert_eq!(requests[5].event.as_deref(), Some("online"));
+        assert_eq!(requests.iter().filter(|i| i.suppressed).count(), 2);
+
+        // Window reopens, the summary doesn't use up the online allowance
+        app_env.rate_limit.max = 3;
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 6);
+        assert!(sent[4].body.contains(r#""event":"suppressed""#));
+        assert!(sent[5].body.contains(r#""event":"online""#));
+
+        // Already reported
+        app_env.rate_limit.max = 4;
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 7);
+        assert!(sent[6].body.contains(r#""event":"online""#));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // A boot loop sends a single escalated alert, then stays quiet until the machine is stable
+    async fn test_request_make_request_boot_loop() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.boot_loop.count = 3;
+
+        for _ in 0..5 {
+            PushRequest::Online
+                .make_request(&app_env, &db)
+                .await
+                .unwrap();
+        }
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 3);
+        assert!(sent[1].body.contains(r#""event":"online""#));
+        assert!(sent[2].body.contains(r#""event":"boot loop""#));
+        assert!(
+            sent[2]
+                .body
+                .contains("test_machine is boot looping, 3 boots in the past 900 seconds @ ")
+        );
+        let boot_loop = ModelBootLoop::get(&db).await.unwrap();
+        assert_eq!(boot_loop.boots, 3);
+
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(requests.len(), 5);
+        assert_eq!(requests[2].event.as_deref(), Some("boot loop"));
+        assert!(requests[2].delivered);
+        assert!(requests[3..].iter().all(|i| i.suppressed && i.muted));
+
+        // Service events aren't affected, and the muted boots are never reported as suppressed
+        PushRequest::Service(Status::Install)
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        assert_eq!(ok.requests().len(), 4);
+        assert!(
+            ok.requests()[3]
+                .body
+                .contains(r#""event":"service installed""#)
+        );
+
+        // No boot for longer than the stable period
+        sqlx::query("UPDATE request SET timestamp = timestamp - $1")
+            .bind(i64::try_from(app_env.boot_loop.stable + 1).unwrap())
+            .execute(&db)
+            .await
+            .unwrap();
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+
+        assert!(ModelBootLoop::get(&db).await.is_none());
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 5);
+        assert!(sent[4].body.contains(r#""event":"online""#));
+        assert!(
+            !sent
+                .iter()
+                .any(|i| i.body.contains(r#""event":"suppressed""#))
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Going offline message includes the uptime & reason, without looking up the public ips
+    async fn test_request_make_request_shutdown() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+
+        PushRequest::Shutdown {
+            uptime: Some(3723),
+            reason: Some("reboot"),
+        }
+        .make_request(&app_env, &db)
+        .await
+        .unwrap();
+        PushRequest::Shutdown {
+            uptime: None,
+            reason: None,
+        }
+        .make_request(&app_env, &db)
+        .await
+        .unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 2);
+        let body = serde_json::from_str::<serde_json::Value>(&sent[0].body).unwrap();
+        assert_eq!(body["event"], "offline");
+        assert_eq!(body["ipv4"], "");
+        assert_eq!(body["ipv6"], "");
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("test_machine going offline, reboot, up 1h 2m 3s @ ")
+        );
+        let body = serde_json::from_str::<serde_json::Value>(&sent[1].body).unwrap();
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("test_machine going offline @ ")
+        );
+
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert!(
+            requests
+                .iter()
+                .all(|i| i.event.as_deref() == Some("offline") && i.delivered)
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // No going offline message while boot looping
+    async fn test_request_make_shutdown_request_boot_loop() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        ModelSkipRequest::update(&db, false).await.unwrap();
+
+        PushRequest::make_shutdown_request(&app_env, &db, Some("reboot"))
+            .await
+            .unwrap();
+        assert_eq!(ok.requests().len(), 1);
+        assert!(ok.requests()[0].body.contains(r#""event":"offline""#));
+
+        ModelBootLoop::insert(&db, 5).await.unwrap();
+        PushRequest::make_shutdown_request(&app_env, &db, Some("reboot"))
+            .await
+            .unwrap();
+        assert_eq!(ok.requests().len(), 1);
+
+        let requests = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[1].suppressed && requests[1].muted);
+        assert_eq!(requests[1].event.as_deref(), Some("offline"));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // The shutdown marker is always recorded, even when requests are skipped
+    async fn test_request_make_shutdown_request_skip() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        assert!(ModelSkipRequest::get(&db).await.unwrap().skip);
+
+        PushRequest::make_shutdown_request(&app_env, &db, Some("reboot"))
+            .await
+            .unwrap();
+
+        assert!(ok.requests().is_empty());
+        assert!(ModelRequest::get_all(&db).await.unwrap().is_empty());
+        assert!(ModelShutdown::get_last(&db).await.unwrap().is_some());
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    #[cfg(target_os = "linux")]
+    // A service restart isn't a shutdown, so a later crash is still reported as unclean
+    async fn test_request_make_shutdown_request_restart() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        ModelSkipRequest::update(&db, false).await.unwrap();
+        let boot = ModelRequest::now() - 600;
+        insert_request(&db, boot, "online").await;
+
+        PushRequest::make_shutdown_request(&app_env, &db, None)
+            .await
+            .unwrap();
+
+        assert!(ok.requests().is_empty());
+        assert!(ModelShutdown::get_last(&db).await.unwrap().is_none());
+        let result = PushRequest::get_last_shutdown(&app_env, &db, boot + 1200)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, LastShutdown::Unclean(_)));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    /// Insert a request with a given timestamp
+    async fn insert_request(db: &SqlitePool, timestamp: u64, event: &str) {
+        let sql = "INSERT INTO request(timestamp, event) VALUES ($1, $2)";
+        sqlx::query(sql)
+            .bind(i64::try_from(timestamp).unwrap())
+            .bind(event)
+            .execute(db)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    // Clean if the shutdown hook ran since the previous boot, the downtime is to this boot
+    async fn test_request_get_last_shutdown() {
+        let (app_env, db, uuid) = setup_test().await;
+        let boot = 1_735_732_800;
+        assert!(
+            PushRequest::get_last_shutdown(&app_env, &db, boot)
+                .await
+                .unwrap()
+                .is_none()
+        );
+
+        insert_request(&db, boot, "online").await;
+        sqlx::query("INSERT INTO shutdown(timestamp, reason) VALUES ($1, 'reboot')")
+            .bind(i64::try_from(boot + 600).unwrap())
+            .execute(&db)
+            .await
+            .unwrap();
+        let result = PushRequest::get_last_shutdown(&app_env, &db, boot + 600 + 3723)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, LastShutdown::Clean(_)));
+        assert_eq!(result.to_string(), "last shutdown clean, down for 1h 2m 3s");
+
+        // Booted again, then never shutdown cleanly, the last request is the last time it was known to be up
+        insert_request(&db, boot + 7200, "online").await;
+        insert_request(&db, boot + 7300, "service installed").await;
+        let result = PushRequest::get_last_shutdown(&app_env, &db, boot + 7300 + 90061)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(result, LastShutdown::Unclean(_)));
+        assert_eq!(
+            result.to_string(),
+            "last shutdown unclean, crashed or lost power, down for up to 1d 1h 1m 1s"
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // The online message says how the previous boot ended
+    async fn test_request_make_request_last_shutdown() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.boot_loop.count = 0;
+
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        sqlx::query("UPDATE request SET timestamp = timestamp - 10")
+            .execute(&db)
+            .await
+            .unwrap();
+        ModelShutdown::insert(&db, None).await.unwrap();
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 3);
+        assert!(
+            sent[0]
+                .body
+                .contains(r#""message":"test_machine online @ "#)
+        );
+        assert!(
+            sent[1]
+                .body
+                .contains(r#""message":"test_machine online, last shutdown clean, down for "#)
+        );
+        assert!(sent[2].body.contains(
+            r#""message":"test_machine online, last shutdown unclean, crashed or lost power, down for up to "#
+        ));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Each boot is recorded, and only ever notified once, even if the service restarts
+    async fn test_request_boot_request_once() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let (v4, v6) = gen_ip_stand_ins().await;
+        app_env.ip_providers = IpProviders::text(&v4.url, &v6.url);
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+
+        PushRequest::boot_request(&app_env, &db, Some("boot_a"))
+            .await
+            .unwrap();
+        PushRequest::boot_request(&app_env, &db, Some("boot_a"))
+            .await
+            .unwrap();
+        assert_eq!(ok.requests().len(), 1);
+
+        let boots = ModelBoot::get_all(&db).await.unwrap();
+        assert_eq!(boots.len(), 1);
+        assert_eq!(boots[0].boot_id, "boot_a");
+        assert_eq!(boots[0].request_id, Some(1));
+        assert_eq!(boots[0].ipv4.as_deref(), Some("127.0.0.1"));
+        assert_eq!(boots[0].ipv6.as_deref(), Some("::1"));
+        assert!(boots[0].notified);
+        #[cfg(target_os = "linux")]
+        assert_eq!(boots[0].kernel, LinuxService::get_kernel());
+
+        // A new boot, which isn't delivered until the outbox is retried
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook("http://127.0.0.1:1"))];
+        let result = PushRequest::boot_request(&app_env, &db, Some("boot_b")).await;
+        assert!(matches!(result, Err(AppError::Undelivered)));
+        let boots = ModelBoot::get_all(&db).await.unwrap();
+        assert_eq!(boots.len(), 2);
+        assert_eq!(boots[1].request_id, Some(2));
+        assert!(!boots[1].notified);
+
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        sqlx::query("UPDATE outbox SET next_attempt = 0")
+            .execute(&db)
+            .await
+            .unwrap();
+        PushRequest::retry_outbox(&app_env, &db).await.unwrap();
+        assert_eq!(ok.requests().len(), 2);
+        assert!(ModelBoot::get_all(&db).await.unwrap()[1].notified);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[test]
+    // Days are the largest unit, and use the calendar of the timezone
+    fn test_request_duration_between() {
+        let app_env = crate::tests::gen_app_env(uuid::Uuid::new_v4());
+        let from = 1_735_732_800;
+        let result = PushRequest::duration_between(&app_env, from, from + 86_400 * 12 + 61);
+        assert_eq!(format!("{result:#}"), "12d 1m 1s");
+        let result = PushRequest::duration_between(&app_env, from, from - 10);
+        assert_eq!(format!("{result:#}"), "0s");
+    }
+
+    #[tokio::test]
+    // Heartbeat is never rate limited
+    async fn test_request_make_request_heartbeat() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.rate_limit.max = 0;
+
+        PushRequest::Heartbeat
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body.contains(r#""event":"heartbeat""#));
+        assert!(
+            sent[0]
+                .body
+                .contains(r#""message":"test_machine still up, uptime "#)
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // The first check only records the ips, an alert is then sent only when an ip changes, with the old & new values
+    async fn test_request_check_public_ip() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let (v4, v6) = gen_ip_stand_ins().await;
+        app_env.ip_providers = IpProviders::text(&v4.url, &v6.url);
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+
+        PushRequest::check_public_ip(&app_env, &db).await.unwrap();
+        PushRequest::check_public_ip(&app_env, &db).await.unwrap();
+        assert!(ok.requests().is_empty());
+        let public_ip = ModelPublicIp::get(&db).await.unwrap().unwrap();
+        assert_eq!(public_ip.ipv4(), Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(public_ip.ipv6(), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+
+        ModelPublicIp::upsert(
+            &db,
+            Some(IpAddr::V4(Ipv4Addr::new(1, 1, 1, 1))),
+            Some(IpAddr::V6(Ipv6Addr::LOCALHOST)),
+        )
+        .await
+        .unwrap();
+        PushRequest::check_public_ip(&app_env, &db).await.unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body.contains(r#""event":"ip changed""#));
+        assert!(sent[0].body.contains(
+            r#""message":"test_machine public ip changed, ipv4 1.1.1.1 -> 127.0.0.1 @ "#
+        ));
+        let public_ip = ModelPublicIp::get(&db).await.unwrap().unwrap();
+        assert_eq!(public_ip.ipv4(), Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+
+        PushRequest::check_public_ip(&app_env, &db).await.unwrap();
+        assert_eq!(ok.requests().len(), 1);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // The first snapshot is only recorded, an alert is then sent only when an interface changes, and never while requests are skipped
+    async fn test_request_check_interfaces() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        let eth0 = |ip: [u8; 4]| Interface {
+            name: S!("eth0"),
+            mac: Some(S!("02:00:00:00:00:01")),
+            state: Some(S!("up")),
+            addresses: vec![IpAddr::V4(Ipv4Addr::from(ip))],
+        };
+
+        PushRequest::check_interfaces(&app_env, &db, vec![eth0([192, 168, 1, 2])])
+            .await
+            .unwrap();
+        PushRequest::check_interfaces(&app_env, &db, vec![eth0([192, 168, 1, 3])])
+            .await
+            .unwrap();
+        assert!(ok.requests().is_empty());
+
+        ModelSkipRequest::update(&db, false).await.unwrap();
+        PushRequest::check_interfaces(&app_env, &db, vec![eth0([192, 168, 1, 3])])
+            .await
+            .unwrap();
+        assert!(ok.requests().is_empty());
+        PushRequest::check_interfaces(&app_env, &db, vec![eth0([192, 168, 1, 4])])
+            .await
+            .unwrap();
+
+        let sent = ok.requests();
+        assert_eq!(sent.len(), 1);
+        assert!(sent[0].body.contains(r#""event":"interface changed""#));
+        assert!(sent[0].body.contains(
+            r#""message":"test_machine interfaces changed, eth0 192.168.1.3 -> 192.168.1.4 @ "#
+        ));
+        let result = ModelInterface::get_all(&db).await.unwrap();
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].addresses, "192.168.1.4");
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // No request made once the attempts are used up, or when there are no providers
+    async fn test_request_get_ip_count() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let result = PushRequest::get_ip(&app_env, 0, Ip::V4).await;
+        assert!(result.unwrap().is_none());
+
+        let stand_in = StandIn::start(200, "127.0.0.1").await;
+        app_env.ip_providers = IpProviders::text(&stand_in.url, &stand_in.url);
+        let result = PushRequest::get_ip(&app_env, 11, Ip::V4).await;
+        assert!(result.unwrap().is_none());
+        assert!(stand_in.requests().is_empty());
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Each provider tried in order, skipping any that fail, or answer with the wrong ip version
+    async fn test_request_get_ip_fallback() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let blocked = StandIn::start(403, "").await;
+        let wrong_version = StandIn::start(200, "::1").await;
+        let ok = StandIn::start(200, r#"{"ip":"127.0.0.1"}"#).await;
+        let unused = StandIn::start(200, "127.0.0.2").await;
+        app_env.ip_providers.v4 = vec![
+            format!("{} text", blocked.url).parse().unwrap(),
+            format!("{} text", wrong_version.url).parse().unwrap(),
+            format!("{} json:ip", ok.url).parse().unwrap(),
+            format!("{} text", unused.url).parse().unwrap(),
+        ];
+
+        let result = PushRequest::get_ip(&app_env, 0, Ip::V4).await.unwrap();
+        assert_eq!(result.unwrap().ip, IpAddr::V4(Ipv4Addr::LOCALHOST));
+        assert_eq!(blocked.requests().len(), 1);
+        assert_eq!(wrong_version.requests().len(), 1);
+        assert_eq!(ok.requests().len(), 1);
+        assert!(unused.requests().is_empty());
+        test_cleanup(uuid, Some(db)).await;
     }
 
     #[tokio::test]
@@ -390,9 +1663,202 @@ mod tests {
     async fn test_request_make_request_count() {}
 
     #[tokio::test]
-    // Request made, and inserted into db
+    // A failing notifier doesn't stop the others from being sent
+    async fn test_request_make_request_fan_out() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![
+            Backend::Slack(Slack {
+                url: Secret::from(C!(err.url)),
+            }),
+            Backend::Webhook(gen_webhook(&ok.url)),
+        ];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        assert_eq!(err.requests().len(), 1);
+        assert_eq!(ok.requests().len(), 1);
+        assert!(ok.requests()[0].body.contains(r#""event":"online""#));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Error returned only when every notifier fails
+    async fn test_request_make_request_undelivered() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![
+            Backend::Slack(Slack {
+                url: Secret::from(C!(err.url)),
+            }),
+            Backend::Webhook(gen_webhook(&err.url)),
+        ];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(matches!(result, Err(AppError::Undelivered)));
+        assert_eq!(err.requests().len(), 2);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Fallbacks are tried in order when a notifier fails, stopping at the first success, and every attempt is stored
+    async fn test_request_make_request_fallback() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![Backend::Slack(Slack {
+            url: Secret::from(C!(err.url)),
+        })];
+        app_env.fallbacks = vec![
+            Backend::Discord(Discord {
+                url: Secret::from(C!(err.url)),
+            }),
+            Backend::Webhook(gen_webhook(&ok.url)),
+            Backend::Gotify(Gotify {
+                url: C!(ok.url),
+                token: Secret::from("token"),
+                title: None,
+                priority: 5,
+                markdown: false,
+            }),
+        ];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        assert_eq!(err.requests().len(), 2);
+        assert_eq!(ok.requests().len(), 1);
+
+        let request = ModelRequest::get_all(&db).await.unwrap();
+        let deliveries = ModelDelivery::get(&db, request[0].request_id)
+            .await
+            .unwrap();
+        assert_eq!(deliveries.len(), 3);
+        assert_eq!(deliveries[0].notifier, "slack");
+        assert!(!deliveries[0].fallback);
+        assert!(!deliveries[0].delivered);
+        assert!(deliveries[0].error.is_some());
+        assert_eq!(deliveries[1].notifier, "discord");
+        assert!(deliveries[1].fallback);
+        assert!(!deliveries[1].delivered);
+        assert_eq!(deliveries[2].notifier, "webhook");
+        assert!(deliveries[2].fallback);
+        assert!(deliveries[2].delivered);
+        assert!(deliveries[2].error.is_none());
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Fallbacks aren't used when any notifier delivers, and it's undelivered if the fallbacks also fail
+    async fn test_request_make_request_fallback_unused() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        app_env.fallbacks = vec![Backend::Slack(Slack {
+            url: Secret::from(C!(err.url)),
+        })];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        assert!(err.requests().is_empty());
+
+        // The primary delivered, so a failing secondary doesn't trigger the fallbacks
+        let fallback = StandIn::start(200, "").await;
+        let pushover = StandIn::start(200, r#"{"status":1,"request":"abc"}"#).await;
+        app_env.notifiers = vec![
+            Backend::Pushover(gen_pushover(&pushover.url)),
+            Backend::Webhook(gen_webhook(&err.url)),
+        ];
+        app_env.fallbacks = vec![Backend::Webhook(gen_webhook(&fallback.url))];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        assert_eq!(pushover.requests().len(), 1);
+        assert_eq!(err.requests().len(), 1);
+        assert!(fallback.requests().is_empty());
+
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&err.url))];
+        app_env.fallbacks = vec![Backend::Slack(Slack {
+            url: Secret::from(C!(err.url)),
+        })];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(matches!(result, Err(AppError::Undelivered)));
+        assert_eq!(err.requests().len(), 3);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // An undelivered message is queued in the outbox, and only marked delivered once a notifier accepts it
+    async fn test_request_retry_outbox() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let ok = StandIn::start(200, "").await;
+        let err = StandIn::start(500, "").await;
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&err.url))];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(matches!(result, Err(AppError::Undelivered)));
+        let outbox = ModelOutbox::get_all(&db).await.unwrap();
+        assert_eq!(outbox.len(), 1);
+        assert_eq!(outbox[0].attempts, 1);
+        assert!(!outbox[0].delivered);
+        let message = outbox[0].get_message().unwrap();
+
+        // Not yet due, so nothing is sent
+        PushRequest::retry_outbox(&app_env, &db).await.unwrap();
+        assert_eq!(err.requests().len(), 1);
+
+        let due = "UPDATE outbox SET next_attempt = 0";
+        sqlx::query(due).execute(&db).await.unwrap();
+        PushRequest::retry_outbox(&app_env, &db).await.unwrap();
+
+        assert_eq!(err.requests().len(), 2);
+        let outbox = ModelOutbox::get_all(&db).await.unwrap();
+        assert_eq!(outbox[0].attempts, 2);
+        assert!(!outbox[0].delivered);
+        assert!(outbox[0].next_attempt > ModelRequest::now());
+
+        app_env.notifiers = vec![Backend::Webhook(gen_webhook(&ok.url))];
+        sqlx::query(due).execute(&db).await.unwrap();
+        PushRequest::retry_outbox(&app_env, &db).await.unwrap();
+
+        let requests = ok.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0].body.contains(&message.timestamp));
+        let outbox = ModelOutbox::get_all(&db).await.unwrap();
+        assert_eq!(outbox[0].attempts, 3);
+        assert!(outbox[0].delivered);
+
+        let deliveries = ModelDelivery::get(&db, outbox[0].request_id).await.unwrap();
+        assert_eq!(deliveries.len(), 3);
+        assert!(deliveries[2].delivered);
+
+        // Delivered messages aren't sent again
+        sqlx::query(due).execute(&db).await.unwrap();
+        PushRequest::retry_outbox(&app_env, &db).await.unwrap();
+        assert_eq!(ok.requests().len(), 1);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Request made, and inserted into db, along with the pushover request id
     async fn test_request_make_request() {
-        let (app_env, db, uuid) = setup_test().await;
+        let (mut app_env, db, uuid) = setup_test().await;
+        let stand_in = StandIn::start(200, r#"{"status":1,"request":"abc"}"#).await;
+        app_env.notifiers = vec![Backend::Pushover(gen_pushover(&stand_in.url))];
 
         let request_len = ModelRequest::get_all(&db).await;
         assert!(request_len.is_ok());
@@ -402,9 +1868,116 @@ mod tests {
 
         assert!(result.is_ok());
 
-        let request_len = ModelRequest::get_all(&db).await;
-        assert!(request_len.is_ok());
-        assert_eq!(request_len.unwrap().len(), 1);
+        let request = ModelRequest::get_all(&db).await;
+        assert!(request.is_ok());
+        let request = request.unwrap();
+        assert_eq!(request.len(), 1);
+        assert!(request[0].delivered);
+        assert_eq!(request[0].pushover_request, Some(S!("abc")));
+        assert_eq!(stand_in.requests().len(), 1);
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Request row is kept when sending fails, but isn't marked as delivered
+    async fn test_request_make_request_pushover_err() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let stand_in = StandIn::start(
+            400,
+            r#"{"token":"invalid","errors":["application token is invalid"],"status":0,"request":"abc"}"#,
+        )
+        .await;
+        app_env.notifiers = vec![Backend::Pushover(gen_pushover(&stand_in.url))];
+
+        let result = PushRequest::Online.make_request(&app_env, &db).await;
+
+        assert!(matches!(result, Err(AppError::Undelivered)));
+        let request = ModelRequest::get_all(&db).await.unwrap();
+        assert_eq!(request.len(), 1);
+        assert!(!request[0].delivered);
+        assert!(request[0].pushover_request.is_none());
+        let deliveries = ModelDelivery::get(&db, request[0].request_id)
+            .await
+            .unwrap();
+        assert_eq!(
+            deliveries[0].error,
+            Some(S!("Pushover error 400: application token is invalid"))
+        );
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // An emergency priority message is delivered, and its receipt polled until acknowledged
+    async fn test_request_poll_receipts() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let stand_in = StandIn::start(
+            200,
+            r#"{"status":1,"request":"abc","receipt":"rcpt","acknowledged":1,"acknowledged_at":1360019238,"acknowledged_by":"user_key","acknowledged_by_device":"iphone","expired":0,"expires_at":1360019290}"#,
+        )
+        .await;
+        app_env.notifiers = vec![Backend::Pushover(Pushover {
+            online: gen_emergency(),
+            ..gen_pushover(&stand_in.url)
+        })];
+
+        PushRequest::Online
+            .make_request(&app_env, &db)
+            .await
+            .unwrap();
+
+        let receipts = ModelReceipt::get_all(&db).await.unwrap();
+        assert_eq!(receipts.len(), 1);
+        assert_eq!(receipts[0].receipt, "rcpt");
+        assert!(!receipts[0].acknowledged);
+
+        let result = PushRequest::poll_receipts(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        let receipts = ModelReceipt::get_all(&db).await.unwrap();
+        assert!(receipts[0].acknowledged);
+        assert_eq!(receipts[0].acknowledged_at, Some(1_360_019_238));
+        assert_eq!(receipts[0].acknowledged_by, Some(S!("user_key")));
+        assert_eq!(receipts[0].acknowledged_by_device, Some(S!("iphone")));
+        let requests = stand_in.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests[0].body.contains("priority=2"));
+        assert!(requests[1].path.starts_with("/1/receipts/rcpt.json"));
+
+        test_cleanup(uuid, Some(db)).await;
+    }
+
+    #[tokio::test]
+    // Receipts that pushover doesn't recognise, or that are too old, stop being polled
+    async fn test_request_poll_receipts_expired() {
+        let (mut app_env, db, uuid) = setup_test().await;
+        let stand_in = StandIn::start(
+            404,
+            r#"{"receipt":"not found","errors":["receipt not found; may be invalid or expired"],"status":0,"request":"abc"}"#,
+        )
+        .await;
+        if let Backend::Pushover(pushover) = &mut app_env.notifiers[0] {
+            pushover.url = C!(stand_in.url);
+        }
+        let request = ModelRequest::insert(&db, "online", false).await.unwrap();
+        ModelReceipt::insert(&db, request.request_id, "rcpt")
+            .await
+            .unwrap();
+        ModelReceipt::insert(&db, request.request_id, "old")
+            .await
+            .unwrap();
+        sqlx::query("UPDATE receipt SET created = 0 WHERE receipt = 'old'")
+            .execute(&db)
+            .await
+            .unwrap();
+
+        let result = PushRequest::poll_receipts(&app_env, &db).await;
+
+        assert!(result.is_ok());
+        let receipts = ModelReceipt::get_all(&db).await.unwrap();
+        assert!(receipts.iter().all(|i| i.expired && !i.acknowledged));
+        assert_eq!(stand_in.requests().len(), 1);
 
         test_cleanup(uuid, Some(db)).await;
     }
@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// Placeholder used in place of a secret in every Debug & Display output
+const REDACTED: &str = "[redacted]";
+
+/// A token, password, or url containing a token, that must never end up in the logs
+/// The only way to get at the value is via `expose()`
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for Secret {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl From<&str> for Secret {
+    fn from(value: &str) -> Self {
+        Self(value.into())
+    }
+}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl fmt::Display for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::C;
+
+    #[test]
+    fn test_secret_redacted() {
+        let secret = Secret::from("hunter2");
+
+        assert_eq!(secret.expose(), "hunter2");
+        assert_eq!(secret.to_string(), "[redacted]");
+        assert_eq!(format!("{secret:?}"), "[redacted]");
+        assert_eq!(
+            format!("{:?}", Some(("user", C!(secret)))),
+            r#"Some(("user", [redacted]))"#
+        );
+    }
+}
@@ -1,7 +1,8 @@
 use crate::app_env::AppEnv;
 use crate::app_error::AppError;
+use crate::interface::Interface;
 use crate::{Code, exit};
-use std::{env, fs, io::Write, path::Path, process::Command};
+use std::{env, fs, io::Write, path::Path, process::Command, time::Duration};
 use tracing::debug;
 
 use super::Service;
@@ -9,6 +10,15 @@ use super::Service;
 const SYSTEMCTL: &str = "systemctl";
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const CHOWN: &str = "chown";
+/// Network interfaces, one directory each
+const SYS_NET: &str = "/sys/class/net";
+/// Systemd targets that are queued when the machine is going down, and the reason each one gives
+const SHUTDOWN_TARGETS: [(&str, &str); 4] = [
+    ("reboot.target", "reboot"),
+    ("poweroff.target", "poweroff"),
+    ("halt.target", "halt"),
+    ("kexec.target", "kexec"),
+];
 
 pub struct LinuxService;
 
@@ -24,6 +34,80 @@ impl LinuxService {
         })
     }
 
+    /// Read a single value from /proc, None if missing or empty
+    fn read_proc(path: &str) -> Option<String> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|i| i.trim().to_owned())
+            .filter(|i| !i.is_empty())
+    }
+
+    /// Unique id of the current boot, changes every time the machine boots
+    pub fn get_boot_id() -> Option<String> {
+        Self::read_proc("/proc/sys/kernel/random/boot_id")
+    }
+
+    /// Kernel release, e.g. 6.1.0-28-amd64
+    pub fn get_kernel() -> Option<String> {
+        Self::read_proc("/proc/sys/kernel/osrelease")
+    }
+
+    pub fn get_hostname() -> Option<String> {
+        Self::read_proc("/proc/sys/kernel/hostname")
+    }
+
+    /// Seconds since boot, the first value in /proc/uptime
+    fn parse_uptime(proc_uptime: &str) -> Option<u64> {
+        proc_uptime
+            .split_whitespace()
+            .next()
+            .and_then(|i| i.parse::<f64>().ok())
+            .filter(|i| i.is_finite() && *i >= 0.0)
+            .map(|i| Duration::from_secs_f64(i).as_secs())
+    }
+
+    /// Get the uptime of the machine, in seconds
+    pub fn get_uptime() -> Option<u64> {
+        Self::read_proc("/proc/uptime").and_then(|i| Self::parse_uptime(&i))
+    }
+
+    /// Each network interface in /sys/class/net, with its mac address & operational state, addresses are found elsewhere
+    pub fn get_links() -> Vec<Interface> {
+        fs::read_dir(SYS_NET).map_or_else(
+            |_| vec![],
+            |dir| {
+                dir.filter_map(Result::ok)
+                    .filter_map(|i| i.file_name().into_string().ok())
+                    .map(|name| Interface {
+                        mac: Self::read_proc(&format!("{SYS_NET}/{name}/address")),
+                        state: Self::read_proc(&format!("{SYS_NET}/{name}/operstate")),
+                        name,
+                        addresses: vec![],
+                    })
+                    .collect()
+            },
+        )
+    }
+
+    /// Find the shutdown target in the output of `systemctl list-jobs`
+    fn parse_shutdown_reason(jobs: &str) -> Option<&'static str> {
+        jobs.lines().find_map(|line| {
+            SHUTDOWN_TARGETS
+                .iter()
+                .find(|(target, _)| line.split_whitespace().any(|i| i == *target))
+                .map(|(_, reason)| *reason)
+        })
+    }
+
+    /// Why the machine is going down, None if it's only the service being stopped
+    pub fn get_shutdown_reason() -> Option<&'static str> {
+        Command::new(SYSTEMCTL)
+            .args(["list-jobs", "--no-legend", "--plain"])
+            .output()
+            .ok()
+            .and_then(|i| Self::parse_shutdown_reason(&String::from_utf8_lossy(&i.stdout)))
+    }
+
     /// Check if we're running as sudo
     fn check_sudo() {
         match sudo::check() {
@@ -44,6 +128,9 @@ impl LinuxService {
     }
 
     /// Create a systemd service file, with correct details
+    /// RemainAfterExit keeps the service active after the online message is sent, so that ExecStop is run at shutdown
+    /// ExecStop can run while the service is still polling receipts, so `--shutdown` doesn't take the instance lock
+    /// TimeoutStopSec leaves a margin over the 10 second shutdown deadline, which includes opening the database
     fn create_service_file(user_name: &str) -> Result<String, AppError> {
         let current_dir = env::current_dir()?.display().to_string();
         Ok(format!(
@@ -55,6 +142,9 @@ StartLimitIntervalSec=0
 
 [Service]
 ExecStart={current_dir}/{APP_NAME}
+ExecStop={current_dir}/{APP_NAME} --shutdown
+RemainAfterExit=yes
+TimeoutStopSec=15
 WorkingDirectory={current_dir}
 SyslogIdentifier={APP_NAME}
 User={user_name}
@@ -170,7 +260,49 @@ mod tests {
         let result = LinuxService::create_service_file("test_user");
         assert!(result.is_ok());
 
-        let expected = "[Unit]\nDescription=sysup\nAfter=network-online.target\nWants=network-online.target\nStartLimitIntervalSec=0\n\n[Service]\nExecStart=/workspaces/sysup/sysup\nWorkingDirectory=/workspaces/sysup\nSyslogIdentifier=sysup\nUser=test_user\nGroup=test_user\nRestart=no\n\n[Install]\nWantedBy=multi-user.target";
+        let expected = "[Unit]\nDescription=sysup\nAfter=network-online.target\nWants=network-online.target\nStartLimitIntervalSec=0\n\n[Service]\nExecStart=/workspaces/sysup/sysup\nExecStop=/workspaces/sysup/sysup --shutdown\nRemainAfterExit=yes\nTimeoutStopSec=15\nWorkingDirectory=/workspaces/sysup\nSyslogIdentifier=sysup\nUser=test_user\nGroup=test_user\nRestart=no\n\n[Install]\nWantedBy=multi-user.target";
         assert_eq!(result.unwrap(), expected);
     }
+
+    #[test]
+    /// Boot id is a uuid, and stays the same for the whole boot
+    fn test_systemd_get_boot_id() {
+        let result = LinuxService::get_boot_id().unwrap();
+        assert_eq!(result.len(), 36);
+        assert_eq!(LinuxService::get_boot_id().unwrap(), result);
+        assert!(LinuxService::read_proc("/proc/sys/kernel/missing").is_none());
+    }
+
+    #[test]
+    /// Every interface found, including loopback, with its mac address & state
+    fn test_systemd_get_links() {
+        let result = LinuxService::get_links();
+        let lo = result.iter().find(|i| i.name == "lo").unwrap();
+        assert_eq!(lo.mac.as_deref(), Some("00:00:00:00:00:00"));
+        assert!(lo.state.is_some());
+        assert!(lo.addresses.is_empty());
+    }
+
+    #[test]
+    /// Uptime is the first value of /proc/uptime, rounded down
+    fn test_systemd_parse_uptime() {
+        assert_eq!(LinuxService::parse_uptime("3723.98 14231.25\n"), Some(3723));
+        assert_eq!(LinuxService::parse_uptime(""), None);
+        assert_eq!(LinuxService::parse_uptime("abc 1.0"), None);
+        assert_eq!(LinuxService::parse_uptime("-1.0 1.0"), None);
+    }
+
+    #[test]
+    /// Shutdown reason found from the queued systemd jobs
+    fn test_systemd_parse_shutdown_reason() {
+        let jobs = "1893 systemd-reboot.service start waiting\n1892 reboot.target start waiting\n1901 sysup.service stop running\n";
+        assert_eq!(LinuxService::parse_shutdown_reason(jobs), Some("reboot"));
+
+        let jobs = "1790 poweroff.target start waiting\n";
+        assert_eq!(LinuxService::parse_shutdown_reason(jobs), Some("poweroff"));
+
+        let jobs = "1901 sysup.service stop running\n";
+        assert_eq!(LinuxService::parse_shutdown_reason(jobs), None);
+        assert_eq!(LinuxService::parse_shutdown_reason(""), None);
+    }
 }
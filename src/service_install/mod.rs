@@ -32,6 +32,7 @@ impl Status {
 }
 
 /// check the cli args, and perform (un)install if necessary
+/// Requests are skipped while the (un)install happens, so that stopping the old service doesn't send a shutdown message
 pub async fn check(
     cli: &CliArgs,
     app_env: &AppEnv,
@@ -39,6 +40,7 @@ pub async fn check(
 ) -> Result<Option<Status>, AppError> {
     if cli.install {
         tracing::info!("Attempting to install service");
+        ModelSkipRequest::update(db, true).await?;
         #[cfg(target_os = "linux")]
         LinuxService::install(app_env)?;
         #[cfg(target_os = "windows")]
@@ -47,11 +49,11 @@ pub async fn check(
         Ok(Some(Status::Install))
     } else if cli.uninstall {
         tracing::info!("Attempting to uninstall service");
+        ModelSkipRequest::update(db, true).await?;
         #[cfg(target_os = "linux")]
         LinuxService::uninstall(app_env)?;
         #[cfg(target_os = "windows")]
         WindowsService::uninstall(app_env)?;
-        ModelSkipRequest::update(db, true).await?;
         Ok(Some(Status::Uninstall))
     } else {
         Ok(None)